@@ -1,6 +1,25 @@
 mod osm4routing;
+pub use crate::osm4routing::categorize::node_properties::{NoNodeProperties, NodeProps};
+pub use crate::osm4routing::categorize::railway::edge_properties::EdgeProperties as RailwayEdgeProperties;
+pub use crate::osm4routing::categorize::road::edge_properties::EdgeProperties as RoadEdgeProperties;
+pub use crate::osm4routing::categorize::rules::{ProfileDefinition, RuleProfile};
+pub use crate::osm4routing::categorize::{node_properties, railway, road, rules};
+pub use crate::osm4routing::clip::{ClipArea, ClipMode};
+pub use crate::osm4routing::components::{ComponentSize, Components};
+pub use crate::osm4routing::conditional::{Condition, Conditional, OpeningHours};
+pub use crate::osm4routing::error::Error;
+pub use crate::osm4routing::graph::{Graph, GraphArc, Route, RouteEdge};
+pub use crate::osm4routing::input::InputFormat;
+pub use crate::osm4routing::isochrone::{Isochrone, ReachedEdge, ReachedNode};
+pub use crate::osm4routing::matching::{
+    match_trace, MapMatch, MatchOptions, MatchedPoint, TracePoint,
+};
 pub use crate::osm4routing::models::*;
-pub use crate::osm4routing::profile_type::ProfileType;
-pub use crate::osm4routing::reader::{read, Reader};
-pub use crate::osm4routing::writers::writer::{write, Writer};
+pub use crate::osm4routing::profile::{Mode, Profile, PropertyValue};
+pub use crate::osm4routing::reader::{
+    read, EdgeIdScheme, EdgeRelations, MissingNodePolicy, Reader,
+};
+pub use crate::osm4routing::sink::{EdgeSink, NodeSink};
+pub use crate::osm4routing::spatial::{EdgeIndex, Snap};
+pub use crate::osm4routing::writers::writer::{write, CsvSink, Writer};
 pub use osmpbfreader::objects::*;
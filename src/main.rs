@@ -1,5 +1,37 @@
-use clap::Parser;
-use osm4routing::ProfileType;
+use clap::{Parser, ValueEnum};
+use osm4routing::{
+    ClipArea, ClipMode, CsvSink, EdgeIdScheme, MissingNodePolicy, Profile, RailwayEdgeProperties,
+    RoadEdgeProperties, RuleProfile,
+};
+use std::collections::HashSet;
+use std::fmt::Display;
+use std::path::PathBuf;
+
+#[derive(Copy, Clone, Debug, PartialEq, ValueEnum)]
+enum ProfileType {
+    Railway,
+    Road,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, ValueEnum)]
+enum Format {
+    Csv,
+    Geojson,
+    Parquet,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, ValueEnum)]
+enum MissingNodes {
+    Abort,
+    Drop,
+    Truncate,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, ValueEnum)]
+enum EdgeIds {
+    Index,
+    Nodes,
+}
 
 /// Extract a road or railway network from Openstreetmap as a graph ready for routing
 #[derive(Parser, Debug)]
@@ -10,21 +42,198 @@ struct Args {
     input: String,
 
     /// Profile type
-    #[arg(short, long)]
-    profile: String,
+    #[arg(short, long, value_enum, required_unless_present = "profile_file")]
+    profile: Option<ProfileType>,
+
+    /// Profile definition file (.toml, .yaml), used instead of a built-in profile
+    #[arg(long, conflicts_with = "profile")]
+    profile_file: Option<String>,
+
+    /// What to do with ways referring to nodes missing from the extract
+    #[arg(long, value_enum, default_value_t = MissingNodes::Abort)]
+    missing_nodes: MissingNodes,
+
+    /// How the edge ids are built: way id and position in the way (index),
+    /// or way id and source and target node ids, stable across extractions (nodes)
+    #[arg(long, value_enum, default_value_t = EdgeIds::Index)]
+    edge_ids: EdgeIds,
+
+    /// Directory where the output files are written
+    #[arg(short, long, default_value = ".")]
+    output_dir: String,
+
+    /// Output format
+    #[arg(short, long, value_enum, default_value_t = Format::Csv)]
+    format: Format,
+
+    /// Merge the edges joined by nodes with only two incident edges when their properties are equal
+    #[arg(long)]
+    contract: bool,
+
+    /// Only extract the ways inside this bounding box (min_lon,min_lat,max_lon,max_lat)
+    #[arg(long, conflicts_with = "clip")]
+    bbox: Option<String>,
+
+    /// Only extract the ways inside the polygons of this file (.geojson, .poly)
+    #[arg(long)]
+    clip: Option<String>,
+
+    /// Cut the ways crossing the boundary of --bbox or --clip instead of keeping them whole
+    #[arg(long)]
+    cut: bool,
+
+    /// Add the connected component of each edge (for each mode) to the output
+    #[arg(long)]
+    components: bool,
+
+    /// Drop the connected components with fewer edges (implies --components)
+    #[arg(long)]
+    min_component_edges: Option<usize>,
+
+    /// Drop the connected components shorter than this total length in meters (implies --components)
+    #[arg(long)]
+    min_component_length: Option<f64>,
+
+    /// Let the modes use the roads with private access (road profile)
+    #[arg(long)]
+    allow_private: bool,
+
+    /// Prefix of the output file names
+    #[arg(long, default_value = "")]
+    prefix: String,
 }
 
 fn main() {
     let args = Args::parse();
 
-    let profile_type = match args.profile.as_str() {
-        "railway" => ProfileType::Railway,
-        "road" => ProfileType::Road,
-        _ => panic!("specified profile isn't implemented"),
+    if let Some(profile_file) = &args.profile_file {
+        match RuleProfile::from_file(profile_file) {
+            Ok(profile) => run(&args, profile),
+            Err(error) => fail(error),
+        }
+        return;
+    }
+
+    match args.profile {
+        Some(ProfileType::Railway) => run(&args, RailwayEdgeProperties::default()),
+        Some(ProfileType::Road) => run(
+            &args,
+            RoadEdgeProperties::default().allow_private(args.allow_private),
+        ),
+        None => fail("a profile or a profile file is required"),
+    }
+}
+
+// Prints the error on stderr and exits with a failure status
+fn fail(error: impl Display) -> ! {
+    eprintln!("Error: {}", error);
+    std::process::exit(1)
+}
+
+fn run<P: Profile>(args: &Args, profile: P) {
+    let missing_node_policy = match args.missing_nodes {
+        MissingNodes::Abort => MissingNodePolicy::Abort,
+        MissingNodes::Drop => MissingNodePolicy::DropWay,
+        MissingNodes::Truncate => MissingNodePolicy::TruncateWay,
+    };
+
+    let edge_id_scheme = match args.edge_ids {
+        EdgeIds::Index => EdgeIdScheme::Index,
+        EdgeIds::Nodes => EdgeIdScheme::Nodes,
+    };
+
+    let area = match (&args.bbox, &args.clip) {
+        (Some(bbox), _) => Some(ClipArea::from_bbox_str(bbox)),
+        (None, Some(clip)) => Some(ClipArea::from_file(clip)),
+        (None, None) => None,
+    };
+    let clip_mode = if args.cut {
+        ClipMode::Cut
+    } else {
+        ClipMode::KeepWhole
     };
 
-    match osm4routing::read(args.input.as_str(), profile_type) {
-        Ok((nodes, edges)) => osm4routing::write(nodes, edges, profile_type),
-        Err(error) => println!("Error: {}", error),
+    let mut reader = osm4routing::Reader::new(profile.clone())
+        .missing_nodes(missing_node_policy)
+        .contract(args.contract)
+        .edge_ids(edge_id_scheme);
+    match area {
+        Some(Ok(area)) => reader = reader.clip(area, clip_mode),
+        Some(Err(error)) => fail(error),
+        None => {}
+    }
+
+    // Only the profiles reading route relations (e.g. railway) write routes.csv
+    let with_routes = !profile.route_types().is_empty();
+    let drop_small = args.min_component_edges.is_some() || args.min_component_length.is_some();
+    let with_components = args.components || drop_small;
+
+    // Without components, the csv files are written while reading instead of holding the graph
+    if args.format == Format::Csv && !with_components {
+        let result = CsvSink::new(&profile, &args.output_dir, &args.prefix).and_then(|mut sink| {
+            let relations = reader.read_into(args.input.as_str(), &mut sink)?;
+            sink.restrictions(&relations.restrictions)?;
+            if with_routes {
+                sink.routes(&relations.routes)?;
+            }
+            sink.finish()
+        });
+        print_result(result);
+        return;
+    }
+
+    let result = reader.read_with_relations(args.input.as_str()).and_then(
+        |(mut nodes, mut edges, mut relations)| {
+            let components =
+                with_components.then(|| osm4routing::Components::compute(&edges, &profile));
+            if let (Some(components), true) = (&components, drop_small) {
+                (nodes, edges) = components.drop_small(
+                    nodes,
+                    edges,
+                    args.min_component_edges,
+                    args.min_component_length,
+                );
+                // Restrictions and routes on dropped edges are dropped too
+                let kept: HashSet<&str> = edges.iter().map(|edge| edge.id.as_str()).collect();
+                relations.restrictions.retain(|restriction| {
+                    [&restriction.from_edge, &restriction.to_edge]
+                        .into_iter()
+                        .chain(&restriction.via_edges)
+                        .all(|edge_id| kept.contains(edge_id.as_str()))
+                });
+                relations
+                    .routes
+                    .retain(|route| kept.contains(route.edge_id.as_str()));
+            }
+
+            let mut writer = osm4routing::Writer::new(nodes, edges, profile)
+                .restrictions(relations.restrictions)
+                .output_dir(&args.output_dir)
+                .prefix(&args.prefix);
+            if let Some(components) = components {
+                writer = writer.components(components);
+            }
+            if with_routes {
+                writer = writer.routes(relations.routes);
+            }
+            match args.format {
+                Format::Csv => writer.to_csv(),
+                Format::Geojson => writer.to_geojson(),
+                Format::Parquet => writer.to_parquet(),
+            }
+        },
+    );
+
+    print_result(result);
+}
+
+fn print_result(result: Result<Vec<PathBuf>, osm4routing::Error>) {
+    match result {
+        Ok(paths) => {
+            for path in paths {
+                println!("{}", path.display())
+            }
+        }
+        Err(error) => fail(error),
     }
 }
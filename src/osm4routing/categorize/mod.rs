@@ -1,3 +1,4 @@
-pub mod edge_properties;
+pub mod node_properties;
 pub mod railway;
 pub mod road;
+pub mod rules;
@@ -0,0 +1,24 @@
+use super::super::profile::PropertyValue;
+
+// NodeProps are the properties of a node of the network, see Profile::NodeProperties
+//
+// The nodes keep them behind an Arc<dyn NodeProps>, so that the models don't depend on the profile
+pub trait NodeProps: Send + Sync {
+    // Infrastructure nodes are always kept as vertices of the graph
+    fn is_infrastructure(&self) -> bool {
+        false
+    }
+
+    // Property values, in the same order as Profile::node_columns
+    fn row(&self) -> Vec<PropertyValue>;
+}
+
+// Node properties of the profiles that don't read the tags of the nodes
+#[derive(Clone, Debug, Default)]
+pub struct NoNodeProperties;
+
+impl NodeProps for NoNodeProperties {
+    fn row(&self) -> Vec<PropertyValue> {
+        Vec::new()
+    }
+}
@@ -1,34 +1,99 @@
-// Edgeself contains what mode can use the edge in each direction
-#[derive(Clone)]
-pub struct EdgeProperties {
-    pub has_railway_tag: bool,
-    pub railway_type: String,
-    pub usage: String,
-    pub service: String,
+use super::super::super::profile::{parse_speed, Profile, PropertyValue};
+use super::node_properties::NodeProperties;
+use osmpbfreader::objects::Tags;
+use serde::Serialize;
+
+// https://wiki.openstreetmap.org/wiki/Key:usage
+#[derive(Clone, Copy, Debug, Default, Serialize, PartialEq)]
+pub enum Usage {
+    #[default]
+    Unknown,
+    Main,
+    Branch,
+    Industrial,
+    Military,
+    Tourism,
+    Scientific,
+    Test,
 }
 
-impl Default for EdgeProperties {
-    fn default() -> EdgeProperties {
-        EdgeProperties {
-            has_railway_tag: false,
-            railway_type: String::new(),
-            usage: String::new(),
-            service: String::new(),
-        }
-    }
+// https://wiki.openstreetmap.org/wiki/Key:service
+#[derive(Clone, Copy, Debug, Default, Serialize, PartialEq)]
+pub enum Service {
+    #[default]
+    Unknown,
+    Siding,
+    Yard,
+    Spur,
+    Crossover,
 }
 
-impl EdgeProperties {
-    pub fn update(&mut self, key_string: String, val_string: String) {
-        let key = key_string.as_str();
-        let val = val_string.as_str();
-        self.update_with_str(key, val);
+// https://wiki.openstreetmap.org/wiki/Key:electrified
+#[derive(Clone, Copy, Debug, Default, Serialize, PartialEq)]
+pub enum Electrified {
+    #[default]
+    Unknown,
+    No,
+    // Electrified with an unknown system
+    Yes,
+    ContactLine,
+    Rail,
+    FourthRail,
+    ContactLineAndRail,
+    GroundLevel,
+}
+
+// Keys of the national train protection systems, e.g. railway:pzb=yes
+// See https://wiki.openstreetmap.org/wiki/OpenRailwayMap/Tagging#Train_protection
+const TRAIN_PROTECTION_SYSTEMS: [&str; 13] = [
+    "atb", "atc", "asfa", "aws", "ctcs", "ebicab", "kvb", "lzb", "pzb", "scmt", "tpws", "tvm",
+    "zub",
+];
+
+// First value of a multi-valued tag ("15000;25000")
+fn first_number(val: &str) -> Option<f64> {
+    val.split(';').next()?.trim().parse().ok()
+}
+
+// Track gauge in millimeters, "standard" being 1435
+fn parse_gauge(val: &str) -> Option<u32> {
+    match val.trim() {
+        "standard" => Some(1435),
+        gauge => gauge.trim_end_matches("mm").trim().parse().ok(),
     }
+}
 
-    pub fn update_with_str(&mut self, key: &str, val: &str) {
+// EdgeProperties describes the track of the edge
+#[derive(Clone, Default, PartialEq)]
+pub struct EdgeProperties {
+    pub has_railway_tag: bool,
+    pub railway_type: String,
+    pub usage: Usage,
+    pub service: Service,
+    // Gauges in millimeters, several for a dual gauge track ("1435;1668")
+    pub gauges: Vec<u32>,
+    pub electrified: Electrified,
+    // Voltage in volts and frequency in hertz (0 for direct current) of the main electrification system
+    pub voltage: Option<f64>,
+    pub frequency: Option<f64>,
+    // Speed limit in km/h
+    pub maxspeed: Option<f64>,
+    pub tracks: Option<u32>,
+    pub highspeed: bool,
+    // Train protection systems (pzb, lzb, etcs…), sorted
+    pub train_protection: Vec<String>,
+    // Highest ETCS level of the track
+    pub etcs_level: Option<u8>,
+    pub track_ref: String,
+}
+
+impl Profile for EdgeProperties {
+    type NodeProperties = NodeProperties;
+
+    fn update_with_str(&mut self, key: &str, val: &str) {
         match key {
             "railway" => {
-                let allowed_railway_types = vec![
+                let allowed_railway_types = [
                     "light_rail",
                     "monorail",
                     "narrow_gauge",
@@ -42,9 +107,158 @@ impl EdgeProperties {
                     self.railway_type = val.to_string()
                 }
             }
-            "usage" => self.usage = val.to_string(),
-            "service" => self.service = val.to_string(),
-            _ => {}
+            "usage" => {
+                self.usage = match val {
+                    "main" => Usage::Main,
+                    "branch" => Usage::Branch,
+                    "industrial" => Usage::Industrial,
+                    "military" => Usage::Military,
+                    "tourism" => Usage::Tourism,
+                    "scientific" => Usage::Scientific,
+                    "test" => Usage::Test,
+                    _ => Usage::Unknown,
+                }
+            }
+            "service" => {
+                self.service = match val {
+                    "siding" => Service::Siding,
+                    "yard" => Service::Yard,
+                    "spur" => Service::Spur,
+                    "crossover" => Service::Crossover,
+                    _ => Service::Unknown,
+                }
+            }
+            "gauge" => self.gauges = val.split(';').filter_map(parse_gauge).collect(),
+            "electrified" => {
+                self.electrified = match val {
+                    "no" => Electrified::No,
+                    "yes" => Electrified::Yes,
+                    "contact_line" => Electrified::ContactLine,
+                    "rail" => Electrified::Rail,
+                    "4th_rail" => Electrified::FourthRail,
+                    "contact_line;rail" | "rail;contact_line" => Electrified::ContactLineAndRail,
+                    "ground-level_power_supply" => Electrified::GroundLevel,
+                    _ => Electrified::Unknown,
+                }
+            }
+            "voltage" => self.voltage = first_number(val),
+            "frequency" => self.frequency = first_number(val),
+            // https://wiki.openstreetmap.org/wiki/Key:maxspeed
+            "maxspeed" => self.maxspeed = parse_speed(val),
+            "railway:track_ref" => self.track_ref = val.to_string(),
+            "tracks" => self.tracks = val.trim().parse().ok(),
+            "highspeed" => self.highspeed = val == "yes",
+            "railway:train_protection" => self.train_protection.extend(
+                val.split(';')
+                    .map(str::trim)
+                    .filter(|system| !system.is_empty())
+                    .map(String::from),
+            ),
+            // Level 0 to 3, "no" when the track isn't equipped
+            "railway:etcs" => {
+                self.etcs_level = val
+                    .split(';')
+                    .filter_map(|level| level.trim().parse::<u8>().ok())
+                    .max();
+                if self.etcs_level.is_some() || val == "yes" {
+                    self.train_protection.push("etcs".to_string());
+                }
+            }
+            _ => {
+                let system = key.strip_prefix("railway:").unwrap_or_default();
+                if TRAIN_PROTECTION_SYSTEMS.contains(&system) && val != "no" {
+                    self.train_protection.push(system.to_string());
+                }
+            }
         }
     }
+
+    // The tags are not read in a given order
+    fn normalize(&mut self) {
+        self.train_protection.sort();
+        self.train_protection.dedup();
+    }
+
+    fn keep(&self) -> bool {
+        self.has_railway_tag
+    }
+
+    fn columns(&self) -> Vec<String> {
+        [
+            "railway_type",
+            "usage",
+            "service",
+            "gauge",
+            "gauges",
+            "electrified",
+            "voltage",
+            "frequency",
+            "maxspeed",
+            "tracks",
+            "highspeed",
+            "train_protection",
+            "etcs_level",
+            "track_ref",
+        ]
+        .map(String::from)
+        .to_vec()
+    }
+
+    // gauge is the first gauge, gauges lists them all separated by ";"
+    fn row(&self) -> Vec<PropertyValue> {
+        let gauges: Vec<String> = self.gauges.iter().map(|gauge| gauge.to_string()).collect();
+        vec![
+            PropertyValue::Category(self.railway_type.clone()),
+            PropertyValue::category(self.usage),
+            PropertyValue::category(self.service),
+            PropertyValue::Number(self.gauges.first().map(|&gauge| gauge as f64)),
+            PropertyValue::Text(gauges.join(";")),
+            PropertyValue::category(self.electrified),
+            PropertyValue::Number(self.voltage),
+            PropertyValue::Number(self.frequency),
+            PropertyValue::Number(self.maxspeed),
+            PropertyValue::Number(self.tracks.map(f64::from)),
+            PropertyValue::Boolean(Some(self.highspeed)),
+            PropertyValue::Category(self.train_protection.join(";")),
+            PropertyValue::Number(self.etcs_level.map(f64::from)),
+            PropertyValue::Text(self.track_ref.clone()),
+        ]
+    }
+
+    // No property of a track depends on the direction
+    fn is_symmetric(&self) -> bool {
+        true
+    }
+
+    // The branches of a switch diverge by a few degrees, going from one to the other is a U-turn
+    fn max_turn_angle(&self) -> Option<f64> {
+        Some(60.)
+    }
+
+    // https://wiki.openstreetmap.org/wiki/Tag:route%3Dtrain and the lines of the other railway types
+    fn route_types(&self) -> Vec<String> {
+        [
+            "train",
+            "railway",
+            "tracks",
+            "light_rail",
+            "subway",
+            "tram",
+            "monorail",
+        ]
+        .map(String::from)
+        .to_vec()
+    }
+
+    fn node_properties(&self, tags: &Tags) -> Option<NodeProperties> {
+        let mut properties = NodeProperties::default();
+        for (key, val) in tags.iter() {
+            properties.update(key.to_string(), val.to_string());
+        }
+        properties.railway_type.is_some().then_some(properties)
+    }
+
+    fn node_columns(&self) -> Vec<String> {
+        NodeProperties::columns()
+    }
 }
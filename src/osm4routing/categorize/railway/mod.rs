@@ -1 +1,2 @@
 pub mod edge_properties;
+pub mod node_properties;
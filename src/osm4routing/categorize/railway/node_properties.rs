@@ -0,0 +1,88 @@
+use super::super::super::profile::PropertyValue;
+use super::super::node_properties::NodeProps;
+use serde::Serialize;
+
+#[derive(Clone, Copy, Debug, Serialize, PartialEq)]
+pub enum RailwayNodeType {
+    Switch,        // https://wiki.openstreetmap.org/wiki/Tag:railway%3Dswitch
+    Signal,        // https://wiki.openstreetmap.org/wiki/Tag:railway%3Dsignal
+    LevelCrossing, // https://wiki.openstreetmap.org/wiki/Tag:railway%3Dlevel_crossing
+    BufferStop,    // https://wiki.openstreetmap.org/wiki/Tag:railway%3Dbuffer_stop
+    Station,       // https://wiki.openstreetmap.org/wiki/Tag:railway%3Dstation
+    Halt,          // https://wiki.openstreetmap.org/wiki/Tag:railway%3Dhalt
+}
+
+// NodeProperties contains the railway infrastructure located on a node of the network
+#[derive(Clone, Default)]
+pub struct NodeProperties {
+    pub railway_type: Option<RailwayNodeType>,
+    pub name: String,
+    pub reference: String,
+    // Kind of switch (default, double_slip, three_way…) from railway:switch
+    pub switch_type: String,
+    // Direction of the signal relative to the way (forward, backward, both)
+    pub signal_direction: String,
+    // Barrier of the level crossing (no, half, full…) from crossing:barrier
+    pub crossing_barrier: String,
+}
+
+impl NodeProperties {
+    pub fn columns() -> Vec<String> {
+        [
+            "railway_type",
+            "name",
+            "ref",
+            "switch_type",
+            "signal_direction",
+            "crossing_barrier",
+        ]
+        .map(String::from)
+        .to_vec()
+    }
+
+    pub fn update(&mut self, key_string: String, val_string: String) {
+        let key = key_string.as_str();
+        let val = val_string.as_str();
+        self.update_with_str(key, val);
+    }
+
+    pub fn update_with_str(&mut self, key: &str, val: &str) {
+        match key {
+            "railway" => match val {
+                "switch" => self.railway_type = Some(RailwayNodeType::Switch),
+                "signal" => self.railway_type = Some(RailwayNodeType::Signal),
+                "level_crossing" => self.railway_type = Some(RailwayNodeType::LevelCrossing),
+                "buffer_stop" => self.railway_type = Some(RailwayNodeType::BufferStop),
+                "station" => self.railway_type = Some(RailwayNodeType::Station),
+                "halt" => self.railway_type = Some(RailwayNodeType::Halt),
+                _ => {}
+            },
+            "name" => self.name = val.to_string(),
+            "ref" | "railway:ref" => self.reference = val.to_string(),
+            "railway:switch" => self.switch_type = val.to_string(),
+            "railway:signal:direction" => self.signal_direction = val.to_string(),
+            "crossing:barrier" => self.crossing_barrier = val.to_string(),
+            _ => {}
+        }
+    }
+}
+
+// Every railway node with properties is an infrastructure
+impl NodeProps for NodeProperties {
+    fn is_infrastructure(&self) -> bool {
+        true
+    }
+
+    fn row(&self) -> Vec<PropertyValue> {
+        vec![
+            self.railway_type
+                .map(PropertyValue::category)
+                .unwrap_or(PropertyValue::Category(String::new())),
+            PropertyValue::Text(self.name.clone()),
+            PropertyValue::Text(self.reference.clone()),
+            PropertyValue::Category(self.switch_type.clone()),
+            PropertyValue::Category(self.signal_direction.clone()),
+            PropertyValue::Category(self.crossing_barrier.clone()),
+        ]
+    }
+}
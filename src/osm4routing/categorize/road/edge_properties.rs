@@ -1,4 +1,9 @@
+use super::super::super::conditional::{parse_conditional, Conditional, OpeningHours};
+use super::super::super::profile::{parse_length, parse_speed, Mode, Profile, PropertyValue};
+use super::super::node_properties::NoNodeProperties;
+use chrono::NaiveDateTime;
 use serde::Serialize;
+use std::borrow::Cow;
 
 #[derive(Clone, Copy, Debug, Serialize, PartialEq)]
 pub enum FootAccessibility {
@@ -35,8 +40,145 @@ pub enum TrainAccessibility {
     Allowed,
 }
 
+// Value of an access tag, for the mode resolved from the hierarchy of the access tags
+// https://wiki.openstreetmap.org/wiki/Key:access
+#[derive(Clone, Copy, Debug, Default, Serialize, PartialEq)]
+pub enum Access {
+    #[default]
+    Yes,
+    // Restricted classes: the mode can only use the edge to reach or leave a place along it
+    Destination,
+    Delivery,
+    Customers,
+    Private,
+    No,
+}
+
+impl Access {
+    pub fn from_tag(val: &str) -> Access {
+        match val {
+            "no" | "false" | "agricultural" | "forestry" | "use_sidepath" => Access::No,
+            "destination" => Access::Destination,
+            "delivery" => Access::Delivery,
+            "customers" => Access::Customers,
+            "private" | "permit" => Access::Private,
+            _ => Access::Yes,
+        }
+    }
+
+    pub fn is_restricted(self) -> bool {
+        matches!(
+            self,
+            Access::Destination | Access::Delivery | Access::Customers | Access::Private
+        )
+    }
+}
+
+// Direction of a oneway, relative to the direction of the way
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Oneway {
+    No,
+    Forward,
+    Backward,
+}
+
+impl Oneway {
+    pub fn from_tag(val: &str) -> Option<Oneway> {
+        match val {
+            "yes" | "true" | "1" => Some(Oneway::Forward),
+            "-1" | "reverse" => Some(Oneway::Backward),
+            "no" | "false" | "0" => Some(Oneway::No),
+            _ => None,
+        }
+    }
+}
+
+// Access and oneway tags of a way, only resolved by normalize as they depend on each other
+// The most specific tag of a mode wins: motorcar, motor_vehicle, vehicle then access for a car
+// https://wiki.openstreetmap.org/wiki/Key:access#Transport_mode_restrictions
+#[derive(Clone, Copy, Default, PartialEq)]
+struct AccessTags {
+    access: Option<Access>,
+    vehicle: Option<Access>,
+    motor_vehicle: Option<Access>,
+    motorcar: Option<Access>,
+    bicycle: Option<Access>,
+    foot: Option<Access>,
+    oneway: Option<Oneway>,
+    oneway_bicycle: Option<Oneway>,
+    roundabout: bool,
+}
+
+impl AccessTags {
+    fn foot(&self) -> Access {
+        self.foot.or(self.access).unwrap_or_default()
+    }
+
+    fn bike(&self) -> Access {
+        self.bicycle
+            .or(self.vehicle)
+            .or(self.access)
+            .unwrap_or_default()
+    }
+
+    fn car(&self) -> Access {
+        self.motorcar
+            .or(self.motor_vehicle)
+            .or(self.vehicle)
+            .or(self.access)
+            .unwrap_or_default()
+    }
+
+    // Roundabouts are oneway unless tagged otherwise
+    fn car_oneway(&self) -> Oneway {
+        self.oneway.unwrap_or(if self.roundabout {
+            Oneway::Forward
+        } else {
+            Oneway::No
+        })
+    }
+
+    fn bike_oneway(&self) -> Oneway {
+        self.oneway_bicycle.unwrap_or(self.car_oneway())
+    }
+}
+
+impl CarAccessibility {
+    // Default speed in km/h when the maxspeed is not tagged
+    pub fn default_speed(self) -> Option<f64> {
+        match self {
+            CarAccessibility::Unknown | CarAccessibility::Forbidden => None,
+            CarAccessibility::Residential => Some(30.),
+            CarAccessibility::Tertiary => Some(50.),
+            CarAccessibility::Secondary => Some(70.),
+            CarAccessibility::Primary => Some(80.),
+            CarAccessibility::Trunk => Some(100.),
+            CarAccessibility::Motorway => Some(120.),
+        }
+    }
+}
+
+// Average speeds in km/h of the modes that don't depend on the road class
+const FOOT_SPEED: f64 = 5.;
+const BIKE_SPEED: f64 = 15.;
+// Routing cost factor of the edges only open to reach a place along them (destination…), so that
+// they are only used at the start or the end of a route
+const RESTRICTED_ACCESS_FACTOR: f64 = 5.;
+
+// Keys whose conditional restrictions are read, grouped by exported column
+const ACCESS_KEYS: [&str; 7] = [
+    "access",
+    "vehicle",
+    "motor_vehicle",
+    "motorcar",
+    "bicycle",
+    "foot",
+    "pedestrian",
+];
+const ONEWAY_KEYS: [&str; 2] = ["oneway", "oneway:bicycle"];
+
 // Edgeself contains what mode can use the edge in each direction
-#[derive(Clone, Copy)]
+#[derive(Clone, PartialEq)]
 pub struct EdgeProperties {
     pub foot: FootAccessibility,
     pub car_forward: CarAccessibility,
@@ -44,6 +186,38 @@ pub struct EdgeProperties {
     pub bike_forward: BikeAccessibility,
     pub bike_backward: BikeAccessibility,
     pub train: TrainAccessibility,
+    // Access of each mode once the access tags are resolved, the restricted classes don't forbid the mode
+    pub foot_access: Access,
+    pub bike_access: Access,
+    pub car_access: Access,
+    // Speed limit in km/h
+    pub maxspeed: Option<f64>,
+    pub lanes: Option<u32>,
+    pub lanes_forward: Option<u32>,
+    pub lanes_backward: Option<u32>,
+    // https://wiki.openstreetmap.org/wiki/Key:surface
+    pub surface: String,
+    // https://wiki.openstreetmap.org/wiki/Key:smoothness
+    pub smoothness: String,
+    // Width in meters
+    pub width: Option<f64>,
+    pub name: String,
+    pub reference: String,
+    pub bridge: bool,
+    pub tunnel: bool,
+    // Vertical position relative to the other ways, 0 when not tagged
+    pub layer: i8,
+    // Whether the modes can use the edges with private access, set on the profile given to the reader
+    pub allow_private: bool,
+    // Conditional restrictions of the access, maxspeed and oneway tags
+    // https://wiki.openstreetmap.org/wiki/Conditional_restrictions
+    pub conditionals: Vec<Conditional>,
+    // The way is closed outside of these hours
+    pub opening_hours: Option<OpeningHours>,
+    tags: AccessTags,
+    // Properties before normalize, to resolve them again at a given time
+    // Only kept for the ways with conditional restrictions or opening hours
+    unconditional: Option<Box<EdgeProperties>>,
 }
 
 impl Default for EdgeProperties {
@@ -55,41 +229,59 @@ impl Default for EdgeProperties {
             bike_forward: BikeAccessibility::Unknown,
             bike_backward: BikeAccessibility::Unknown,
             train: TrainAccessibility::Unknown,
+            foot_access: Access::Yes,
+            bike_access: Access::Yes,
+            car_access: Access::Yes,
+            maxspeed: None,
+            lanes: None,
+            lanes_forward: None,
+            lanes_backward: None,
+            surface: String::new(),
+            smoothness: String::new(),
+            width: None,
+            name: String::new(),
+            reference: String::new(),
+            bridge: false,
+            tunnel: false,
+            layer: 0,
+            allow_private: false,
+            conditionals: Vec::new(),
+            opening_hours: None,
+            tags: AccessTags::default(),
+            unconditional: None,
         }
     }
 }
 
 impl EdgeProperties {
-    // Normalize fills UNKNOWN fields
-    pub fn normalize(&mut self) {
-        if self.car_backward == CarAccessibility::Unknown {
-            self.car_backward = self.car_forward;
-        }
-        if self.bike_backward == BikeAccessibility::Unknown {
-            self.bike_backward = self.bike_forward;
-        }
-        if self.car_forward == CarAccessibility::Unknown {
-            self.car_forward = CarAccessibility::Forbidden;
+    // Lets the modes use the private roads, e.g. to route within a private estate
+    pub fn allow_private(mut self, allow_private: bool) -> Self {
+        self.allow_private = allow_private;
+        self
+    }
+
+    fn access(&self, mode: Mode) -> Access {
+        match mode {
+            Mode::Foot => self.foot_access,
+            Mode::Bike => self.bike_access,
+            Mode::Car => self.car_access,
         }
+    }
+
+    // Whether the mode can use the edge with its access, access=no being already resolved by normalize
+    fn access_allowed(&self, mode: Mode) -> bool {
+        self.allow_private || self.access(mode) != Access::Private
+    }
+
+    // Highways only give the bike accessibility when no cycle lane or track was tagged
+    fn bike_default(&mut self, bike: BikeAccessibility) {
         if self.bike_forward == BikeAccessibility::Unknown {
-            self.bike_forward = BikeAccessibility::Forbidden;
-        }
-        if self.car_backward == CarAccessibility::Unknown {
-            self.car_backward = CarAccessibility::Forbidden;
-        }
-        if self.bike_backward == BikeAccessibility::Unknown {
-            self.bike_backward = BikeAccessibility::Forbidden;
-        }
-        if self.foot == FootAccessibility::Unknown {
-            self.foot = FootAccessibility::Forbidden;
-        }
-        if self.train == TrainAccessibility::Unknown {
-            self.train = TrainAccessibility::Forbidden;
+            self.bike_forward = bike;
         }
     }
 
     // Accessible means that at least one mean of transportation can use it in one direction
-    pub fn accessible(self) -> bool {
+    pub fn accessible(&self) -> bool {
         self.bike_forward != BikeAccessibility::Forbidden
             || self.bike_backward != BikeAccessibility::Forbidden
             || self.car_forward != CarAccessibility::Forbidden
@@ -98,13 +290,48 @@ impl EdgeProperties {
             || self.train != TrainAccessibility::Forbidden
     }
 
-    pub fn update(&mut self, key_string: String, val_string: String) {
-        let key = key_string.as_str();
-        let val = val_string.as_str();
-        self.update_with_str(key, val);
+    // Properties at the local time: the conditional restrictions that hold replace the plain tags,
+    // and every mode is forbidden outside of the opening hours
+    pub fn at(&self, time: &NaiveDateTime) -> Cow<'_, EdgeProperties> {
+        let Some(unconditional) = &self.unconditional else {
+            return Cow::Borrowed(self);
+        };
+        let mut properties = (**unconditional).clone();
+        // When several restrictions hold, the last one wins
+        for conditional in self.conditionals.iter().filter(|c| c.holds(time)) {
+            properties.update_with_str(&conditional.key, &conditional.value);
+        }
+        properties.normalize();
+        if self
+            .opening_hours
+            .as_ref()
+            .is_some_and(|opening_hours| !opening_hours.is_open(time))
+        {
+            properties.foot = FootAccessibility::Forbidden;
+            properties.car_forward = CarAccessibility::Forbidden;
+            properties.car_backward = CarAccessibility::Forbidden;
+            properties.bike_forward = BikeAccessibility::Forbidden;
+            properties.bike_backward = BikeAccessibility::Forbidden;
+        }
+        Cow::Owned(properties)
+    }
+
+    // Conditional restrictions of the given keys, separated by ";"
+    fn conditionals_of(&self, keys: &[&str]) -> PropertyValue {
+        let conditionals: Vec<String> = self
+            .conditionals
+            .iter()
+            .filter(|conditional| keys.contains(&conditional.key.as_str()))
+            .map(|conditional| conditional.to_string())
+            .collect();
+        PropertyValue::Text(conditionals.join("; "))
     }
+}
+
+impl Profile for EdgeProperties {
+    type NodeProperties = NoNodeProperties;
 
-    pub fn update_with_str(&mut self, key: &str, val: &str) {
+    fn update_with_str(&mut self, key: &str, val: &str) {
         match key {
             "highway" => match val {
                 "cycleway" => {
@@ -112,45 +339,46 @@ impl EdgeProperties {
                     self.foot = FootAccessibility::Allowed;
                 }
                 "path" | "footway" | "steps" | "pedestrian" => {
-                    self.bike_forward = BikeAccessibility::Allowed;
+                    self.bike_default(BikeAccessibility::Allowed);
                     self.foot = FootAccessibility::Allowed;
                 }
                 "primary" | "primary_link" => {
                     self.car_forward = CarAccessibility::Primary;
                     self.foot = FootAccessibility::Allowed;
-                    self.bike_forward = BikeAccessibility::Allowed;
+                    self.bike_default(BikeAccessibility::Allowed);
                 }
                 "secondary" => {
                     self.car_forward = CarAccessibility::Secondary;
                     self.foot = FootAccessibility::Allowed;
-                    self.bike_forward = BikeAccessibility::Allowed;
+                    self.bike_default(BikeAccessibility::Allowed);
                 }
                 "tertiary" => {
                     self.car_forward = CarAccessibility::Tertiary;
                     self.foot = FootAccessibility::Allowed;
-                    self.bike_forward = BikeAccessibility::Allowed;
+                    self.bike_default(BikeAccessibility::Allowed);
                 }
                 "unclassified" | "residential" | "living_street" | "road" | "service" | "track" => {
                     self.car_forward = CarAccessibility::Residential;
                     self.foot = FootAccessibility::Allowed;
-                    self.bike_forward = BikeAccessibility::Allowed;
+                    self.bike_default(BikeAccessibility::Allowed);
                 }
                 "motorway" | "motorway_link" => {
                     self.car_forward = CarAccessibility::Motorway;
                     self.foot = FootAccessibility::Forbidden;
-                    self.bike_forward = BikeAccessibility::Forbidden;
+                    self.bike_default(BikeAccessibility::Forbidden);
                 }
                 "trunk" | "trunk_link" => {
                     self.car_forward = CarAccessibility::Trunk;
                     self.foot = FootAccessibility::Forbidden;
-                    self.bike_forward = BikeAccessibility::Forbidden;
+                    self.bike_default(BikeAccessibility::Forbidden);
                 }
                 _ => {}
             },
-            "pedestrian" | "foot" => match val {
-                "no" => self.foot = FootAccessibility::Forbidden,
-                _ => self.foot = FootAccessibility::Allowed,
-            },
+            "pedestrian" | "foot" => self.tags.foot = Some(Access::from_tag(val)),
+            "access" => self.tags.access = Some(Access::from_tag(val)),
+            "vehicle" => self.tags.vehicle = Some(Access::from_tag(val)),
+            "motor_vehicle" => self.tags.motor_vehicle = Some(Access::from_tag(val)),
+            "motorcar" => self.tags.motorcar = Some(Access::from_tag(val)),
 
             // http://wiki.openstreetmap.org/wiki/Cycleway
             // http://wiki.openstreetmap.org/wiki/Map_Features#Cycleway
@@ -163,37 +391,307 @@ impl EdgeProperties {
                 _ => self.bike_forward = BikeAccessibility::Lane,
             },
 
-            "bicycle" => match val {
-                "no" | "false" => self.bike_forward = BikeAccessibility::Forbidden,
-                _ => self.bike_forward = BikeAccessibility::Allowed,
-            },
+            "bicycle" => self.tags.bicycle = Some(Access::from_tag(val)),
             "busway" => match val {
                 "opposite_lane" | "opposite_track" => {
                     self.bike_backward = BikeAccessibility::Busway
                 }
                 _ => self.bike_forward = BikeAccessibility::Busway,
             },
-            "oneway" => match val {
-                "yes" | "true" | "1" => {
-                    self.car_backward = CarAccessibility::Forbidden;
-                    if self.bike_backward == BikeAccessibility::Unknown {
-                        self.bike_backward = BikeAccessibility::Forbidden;
-                    }
+            // https://wiki.openstreetmap.org/wiki/Key:oneway
+            "oneway" => self.tags.oneway = Oneway::from_tag(val),
+            "oneway:bicycle" => self.tags.oneway_bicycle = Oneway::from_tag(val),
+            "junction" if val == "roundabout" => self.tags.roundabout = true,
+            "railway" => {
+                self.train = TrainAccessibility::Allowed;
+            }
+            // https://wiki.openstreetmap.org/wiki/Key:maxspeed
+            "maxspeed" => self.maxspeed = parse_speed(val),
+            "lanes" => self.lanes = val.trim().parse().ok(),
+            "lanes:forward" => self.lanes_forward = val.trim().parse().ok(),
+            "lanes:backward" => self.lanes_backward = val.trim().parse().ok(),
+            "surface" => self.surface = val.to_string(),
+            "smoothness" => self.smoothness = val.to_string(),
+            "width" => self.width = parse_length(val),
+            "name" => self.name = val.to_string(),
+            "ref" => self.reference = val.to_string(),
+            "bridge" => self.bridge = val != "no",
+            "tunnel" => self.tunnel = val != "no",
+            "layer" => self.layer = val.trim().parse().unwrap_or_default(),
+            // https://wiki.openstreetmap.org/wiki/Key:opening_hours
+            "opening_hours" => self.opening_hours = OpeningHours::parse(val),
+            _ => match key.strip_suffix(":conditional") {
+                Some(key)
+                    if ACCESS_KEYS.contains(&key)
+                        || ONEWAY_KEYS.contains(&key)
+                        || key == "maxspeed" =>
+                {
+                    self.conditionals.extend(parse_conditional(key, val))
                 }
                 _ => {}
             },
-            "junction" => {
-                if val == "roundabout" {
-                    self.car_backward = CarAccessibility::Forbidden;
-                    if self.bike_backward == BikeAccessibility::Unknown {
-                        self.bike_backward = BikeAccessibility::Forbidden;
-                    }
+        }
+    }
+
+    // Normalize resolves the access and oneway tags, and fills UNKNOWN fields
+    fn normalize(&mut self) {
+        if (!self.conditionals.is_empty() || self.opening_hours.is_some())
+            && self.unconditional.is_none()
+        {
+            let mut unconditional = self.clone();
+            unconditional.conditionals.clear();
+            unconditional.opening_hours = None;
+            self.unconditional = Some(Box::new(unconditional));
+        }
+
+        // Forgotten once resolved, so that edges with the same result have equal properties
+        let tags = std::mem::take(&mut self.tags);
+
+        // The tag of the mode itself can open a way that its highway type forbids, e.g. foot=yes on a trunk
+        match tags.foot {
+            Some(Access::No) => self.foot = FootAccessibility::Forbidden,
+            Some(_) => self.foot = FootAccessibility::Allowed,
+            None => {}
+        }
+        if tags.bicycle.is_some_and(|access| access != Access::No)
+            && matches!(
+                self.bike_forward,
+                BikeAccessibility::Unknown | BikeAccessibility::Forbidden
+            )
+        {
+            self.bike_forward = BikeAccessibility::Allowed;
+        }
+
+        match tags.car_oneway() {
+            Oneway::Forward => self.car_backward = CarAccessibility::Forbidden,
+            Oneway::Backward => {
+                self.car_backward = self.car_forward;
+                self.car_forward = CarAccessibility::Forbidden;
+            }
+            Oneway::No => {}
+        }
+        match tags.bike_oneway() {
+            Oneway::Forward => {
+                if self.bike_backward == BikeAccessibility::Unknown {
+                    self.bike_backward = BikeAccessibility::Forbidden;
                 }
             }
-            "railway" => {
-                self.train = TrainAccessibility::Allowed;
+            Oneway::Backward => {
+                if self.bike_backward == BikeAccessibility::Unknown {
+                    self.bike_backward = self.bike_forward;
+                }
+                if self.bike_forward == BikeAccessibility::Allowed {
+                    self.bike_forward = BikeAccessibility::Forbidden;
+                }
+            }
+            Oneway::No => {}
+        }
+
+        if self.car_backward == CarAccessibility::Unknown {
+            self.car_backward = self.car_forward;
+        }
+        if self.bike_backward == BikeAccessibility::Unknown {
+            self.bike_backward = self.bike_forward;
+        }
+        if self.car_forward == CarAccessibility::Unknown {
+            self.car_forward = CarAccessibility::Forbidden;
+        }
+        if self.bike_forward == BikeAccessibility::Unknown {
+            self.bike_forward = BikeAccessibility::Forbidden;
+        }
+        if self.car_backward == CarAccessibility::Unknown {
+            self.car_backward = CarAccessibility::Forbidden;
+        }
+        if self.bike_backward == BikeAccessibility::Unknown {
+            self.bike_backward = BikeAccessibility::Forbidden;
+        }
+        if self.foot == FootAccessibility::Unknown {
+            self.foot = FootAccessibility::Forbidden;
+        }
+        if self.train == TrainAccessibility::Unknown {
+            self.train = TrainAccessibility::Forbidden;
+        }
+
+        // A generic access=no also forbids the modes, except on the cycle lanes and tracks of the way
+        self.foot_access = tags.foot();
+        self.bike_access = tags.bike();
+        self.car_access = tags.car();
+        if self.foot_access == Access::No {
+            self.foot = FootAccessibility::Forbidden;
+        }
+        if self.bike_access == Access::No {
+            for bike in [&mut self.bike_forward, &mut self.bike_backward] {
+                if *bike == BikeAccessibility::Allowed {
+                    *bike = BikeAccessibility::Forbidden;
+                }
+            }
+        }
+        if self.car_access == Access::No {
+            self.car_forward = CarAccessibility::Forbidden;
+            self.car_backward = CarAccessibility::Forbidden;
+        }
+    }
+
+    // A way closed by its plain tags is kept when a conditional restriction can open it
+    fn keep(&self) -> bool {
+        self.accessible()
+            || self.unconditional.as_ref().is_some_and(|unconditional| {
+                self.conditionals.iter().any(|conditional| {
+                    let mut properties = (**unconditional).clone();
+                    properties.update_with_str(&conditional.key, &conditional.value);
+                    properties.normalize();
+                    properties.accessible()
+                })
+            })
+    }
+
+    fn columns(&self) -> Vec<String> {
+        [
+            "foot",
+            "car_forward",
+            "car_backward",
+            "bike_forward",
+            "bike_backward",
+            "train",
+            "foot_access",
+            "bike_access",
+            "car_access",
+            "maxspeed",
+            "lanes",
+            "lanes_forward",
+            "lanes_backward",
+            "surface",
+            "smoothness",
+            "width",
+            "name",
+            "ref",
+            "bridge",
+            "tunnel",
+            "layer",
+            "access_conditional",
+            "maxspeed_conditional",
+            "oneway_conditional",
+            "opening_hours",
+        ]
+        .map(String::from)
+        .to_vec()
+    }
+
+    fn row(&self) -> Vec<PropertyValue> {
+        vec![
+            PropertyValue::category(self.foot),
+            PropertyValue::category(self.car_forward),
+            PropertyValue::category(self.car_backward),
+            PropertyValue::category(self.bike_forward),
+            PropertyValue::category(self.bike_backward),
+            PropertyValue::category(self.train),
+            PropertyValue::category(self.foot_access),
+            PropertyValue::category(self.bike_access),
+            PropertyValue::category(self.car_access),
+            PropertyValue::Number(self.maxspeed),
+            PropertyValue::Number(self.lanes.map(f64::from)),
+            PropertyValue::Number(self.lanes_forward.map(f64::from)),
+            PropertyValue::Number(self.lanes_backward.map(f64::from)),
+            PropertyValue::Category(self.surface.clone()),
+            PropertyValue::Category(self.smoothness.clone()),
+            PropertyValue::Number(self.width),
+            PropertyValue::Text(self.name.clone()),
+            PropertyValue::Text(self.reference.clone()),
+            PropertyValue::Boolean(Some(self.bridge)),
+            PropertyValue::Boolean(Some(self.tunnel)),
+            PropertyValue::Number(Some(self.layer as f64)),
+            self.conditionals_of(&ACCESS_KEYS),
+            self.conditionals_of(&["maxspeed"]),
+            self.conditionals_of(&ONEWAY_KEYS),
+            PropertyValue::Text(
+                self.opening_hours
+                    .as_ref()
+                    .map(|opening_hours| opening_hours.to_string())
+                    .unwrap_or_default(),
+            ),
+        ]
+    }
+
+    fn modes(&self) -> Vec<Mode> {
+        vec![Mode::Foot, Mode::Bike, Mode::Car]
+    }
+
+    // Speed in km/h of the mode on this edge, None if the mode can't use it in any direction
+    fn speed(&self, mode: Mode) -> Option<f64> {
+        if !self.access_allowed(mode) {
+            return None;
+        }
+        match mode {
+            Mode::Foot if self.foot != FootAccessibility::Forbidden => Some(FOOT_SPEED),
+            Mode::Bike
+                if self.bike_forward != BikeAccessibility::Forbidden
+                    || self.bike_backward != BikeAccessibility::Forbidden =>
+            {
+                Some(BIKE_SPEED)
+            }
+            Mode::Car => {
+                let class = if self.car_forward != CarAccessibility::Forbidden {
+                    self.car_forward
+                } else {
+                    self.car_backward
+                };
+                let default_speed = class.default_speed()?;
+                Some(self.maxspeed.unwrap_or(default_speed))
             }
-            _ => {}
+            _ => None,
         }
     }
+
+    fn forward(&self, mode: Mode) -> bool {
+        self.access_allowed(mode)
+            && match mode {
+                Mode::Foot => self.foot != FootAccessibility::Forbidden,
+                Mode::Bike => self.bike_forward != BikeAccessibility::Forbidden,
+                Mode::Car => self.car_forward != CarAccessibility::Forbidden,
+            }
+    }
+
+    fn backward(&self, mode: Mode) -> bool {
+        self.access_allowed(mode)
+            && match mode {
+                Mode::Foot => self.foot != FootAccessibility::Forbidden,
+                Mode::Bike => self.bike_backward != BikeAccessibility::Forbidden,
+                Mode::Car => self.car_backward != CarAccessibility::Forbidden,
+            }
+    }
+
+    fn cost_factor(&self, mode: Mode) -> f64 {
+        if self.access(mode).is_restricted() {
+            RESTRICTED_ACCESS_FACTOR
+        } else {
+            1.
+        }
+    }
+
+    // The road profile has always named it wkt
+    fn geometry_column(&self) -> &str {
+        "wkt"
+    }
+
+    fn is_symmetric(&self) -> bool {
+        self.car_forward == self.car_backward
+            && self.bike_forward == self.bike_backward
+            && self.lanes_forward == self.lanes_backward
+            && !self
+                .conditionals
+                .iter()
+                .any(|conditional| ONEWAY_KEYS.contains(&conditional.key.as_str()))
+    }
+
+    fn speed_at(&self, mode: Mode, time: &NaiveDateTime) -> Option<f64> {
+        self.at(time).speed(mode)
+    }
+
+    fn forward_at(&self, mode: Mode, time: &NaiveDateTime) -> bool {
+        self.at(time).forward(mode)
+    }
+
+    fn backward_at(&self, mode: Mode, time: &NaiveDateTime) -> bool {
+        self.at(time).backward(mode)
+    }
 }
@@ -0,0 +1,874 @@
+use super::super::error::Error;
+use super::super::models::node::Node;
+use super::super::profile::{parse_length, parse_speed, Mode, Profile, PropertyValue};
+use super::railway::edge_properties::EdgeProperties as RailwayEdgeProperties;
+use super::railway::node_properties::NodeProperties as RailwayNodeProperties;
+use osmpbfreader::objects::Tags;
+use serde::Deserialize;
+use std::collections::{BTreeMap, HashMap};
+use std::path::Path;
+use std::sync::Arc;
+
+// How the value of a property is interpreted when it is written
+#[derive(Clone, Copy, Debug, Default, Deserialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum PropertyKind {
+    #[default]
+    Category,
+    Text,
+    Number,
+    // Speed in km/h, tag values in mph ("30 mph") are converted when assigned
+    Speed,
+    // Length in meters, values with a unit ("3.5 m", 12'6") are converted when assigned
+    Length,
+    Boolean,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct PropertyDefinition {
+    pub name: String,
+    #[serde(rename = "type", default)]
+    pub kind: PropertyKind,
+    #[serde(default)]
+    pub default: String,
+    // Properties only used by the rules (e.g. maxspeed for the speeds) can be left out of the output
+    #[serde(default = "yes")]
+    pub write: bool,
+}
+
+fn yes() -> bool {
+    true
+}
+
+// Condition on the current value of a property
+#[derive(Clone, Debug, Deserialize)]
+pub struct Condition {
+    pub property: String,
+    #[serde(default)]
+    pub is: Option<String>,
+    #[serde(default)]
+    pub not: Option<String>,
+    // Name of another property that must have the same value
+    #[serde(default)]
+    pub same_as: Option<String>,
+}
+
+// Transformation of a value in `set`, e.g. "$value | first"
+// The values are seen as lists separated by ";", like the multi-valued tags
+#[derive(Clone, Debug, PartialEq)]
+enum Transform {
+    // The trimmed items, without the empty ones
+    Split,
+    // The first item
+    First,
+    // The highest item that is a number
+    Max,
+    // The items sorted, without duplicates
+    Sort,
+    // The items that are non-negative integers
+    Integers,
+    // The items equal to the first argument replaced by the second, e.g. replace(standard, 1435)
+    Replace(String, String),
+    // The suffix removed from the items, e.g. strip(mm)
+    Strip(String),
+}
+
+impl Transform {
+    fn parse(text: &str) -> Option<Transform> {
+        let text = text.trim();
+        let (name, arguments) = match text.strip_suffix(')').and_then(|t| t.split_once('(')) {
+            Some((name, arguments)) => (name.trim(), arguments.split(',').map(str::trim).collect()),
+            None => (text, Vec::new()),
+        };
+        match (name, arguments.as_slice()) {
+            ("split", []) => Some(Transform::Split),
+            ("first", []) => Some(Transform::First),
+            ("max", []) => Some(Transform::Max),
+            ("sort", []) => Some(Transform::Sort),
+            ("integers", []) => Some(Transform::Integers),
+            ("replace", [from, to]) => Some(Transform::Replace(from.to_string(), to.to_string())),
+            ("strip", [suffix]) => Some(Transform::Strip(suffix.to_string())),
+            _ => None,
+        }
+    }
+
+    fn apply(&self, value: &str) -> String {
+        let items = || {
+            value
+                .split(';')
+                .map(str::trim)
+                .filter(|item| !item.is_empty())
+        };
+        match self {
+            Transform::Split => items().collect::<Vec<_>>().join(";"),
+            Transform::First => value
+                .split(';')
+                .next()
+                .unwrap_or_default()
+                .trim()
+                .to_string(),
+            Transform::Max => items()
+                .filter_map(|item| item.parse::<f64>().ok())
+                .max_by(f64::total_cmp)
+                .map(|max| max.to_string())
+                .unwrap_or_default(),
+            Transform::Sort => {
+                let mut items: Vec<&str> = items().collect();
+                items.sort_unstable();
+                items.dedup();
+                items.join(";")
+            }
+            Transform::Integers => items()
+                .filter(|item| item.parse::<u32>().is_ok())
+                .collect::<Vec<_>>()
+                .join(";"),
+            Transform::Replace(from, to) => items()
+                .map(|item| if item == from { to.as_str() } else { item })
+                .collect::<Vec<_>>()
+                .join(";"),
+            Transform::Strip(suffix) => items()
+                .map(|item| item.trim_end_matches(suffix.as_str()).trim())
+                .collect::<Vec<_>>()
+                .join(";"),
+        }
+    }
+}
+
+// Splits a value of `set` into its source ("$value", "$key", "$<property>" or a literal) and its
+// transforms, only the values starting with "$" have transforms
+fn parse_expression(expression: &str) -> (&str, Vec<&str>) {
+    if !expression.starts_with('$') {
+        return (expression, Vec::new());
+    }
+    let mut parts = expression.split('|');
+    let source = parts.next().unwrap_or_default().trim();
+    (source, parts.collect())
+}
+
+// Rule applied to every tag of a way whose key matches and whose value is accepted
+//
+// In `set` and `append`, "$value" is replaced by the value of the tag, "$key" by the key (the part
+// matched by "*" for a prefix) and "$<property>" by the value of that property, followed by
+// transforms separated by "|", e.g. "$value | first"
+#[derive(Clone, Debug, Deserialize)]
+pub struct Rule {
+    // Key of the tag, or prefix of the key ending with "*", e.g. "railway:*"
+    pub key: String,
+    // With a prefix, the accepted parts of the key matched by "*", any part when empty
+    #[serde(default)]
+    pub suffixes: Vec<String>,
+    // Accepted tag values, every value is accepted when empty
+    #[serde(default)]
+    pub values: Vec<String>,
+    // Tag values that never match the rule
+    #[serde(default)]
+    pub except: Vec<String>,
+    // Every condition must hold for the rule to apply
+    #[serde(default)]
+    pub when: Vec<Condition>,
+    #[serde(default)]
+    pub set: BTreeMap<String, String>,
+    // Values added to list properties, separated by ";", e.g. the train protection systems
+    #[serde(default)]
+    pub append: BTreeMap<String, String>,
+    // Drop the whole way
+    #[serde(default)]
+    pub reject: bool,
+}
+
+// Assignment applied once all the tags of a way have been read, to fill the unknown values
+#[derive(Clone, Debug, Deserialize)]
+pub struct Normalization {
+    #[serde(default)]
+    pub when: Vec<Condition>,
+    pub set: BTreeMap<String, String>,
+}
+
+// Speed of a mode when every condition holds; the first matching entry of a mode wins
+#[derive(Clone, Debug, Deserialize)]
+pub struct SpeedDefinition {
+    pub mode: String,
+    #[serde(default)]
+    pub when: Vec<Condition>,
+    pub speed: f64,
+    // Speed property that replaces the speed when it is set, e.g. the tagged maxspeed
+    #[serde(default)]
+    pub maxspeed: Option<String>,
+}
+
+// Factor of the travel time of a mode in the routing costs when every condition holds; the first
+// matching entry of a mode wins, the factor is 1 without any
+#[derive(Clone, Debug, Deserialize)]
+pub struct CostDefinition {
+    pub mode: String,
+    #[serde(default)]
+    pub when: Vec<Condition>,
+    pub factor: f64,
+}
+
+// Conditions for a mode to travel along the edge (forward) and against it (backward)
+// Without it, the mode can travel in both directions when it has a speed
+#[derive(Clone, Debug, Deserialize)]
+pub struct DirectionDefinition {
+    pub mode: String,
+    #[serde(default)]
+    pub forward: Vec<Condition>,
+    #[serde(default)]
+    pub backward: Vec<Condition>,
+}
+
+// ProfileDefinition is the content of a profile file
+#[derive(Clone, Debug, Deserialize)]
+pub struct ProfileDefinition {
+    // Built-in node properties to use ("railway"), nodes have no property when missing
+    #[serde(default)]
+    pub nodes: Option<String>,
+    // Values of the route tag of the relations written as routes of the edges
+    #[serde(default)]
+    pub routes: Vec<String>,
+    // Sharpest turn in degrees between two consecutive edges, for the map matching
+    #[serde(default)]
+    pub max_turn_angle: Option<f64>,
+    // Name of the WKT geometry column of edges.csv, "geometry" when missing
+    #[serde(default)]
+    pub geometry_column: Option<String>,
+    pub properties: Vec<PropertyDefinition>,
+    #[serde(default)]
+    pub rules: Vec<Rule>,
+    #[serde(default)]
+    pub normalize: Vec<Normalization>,
+    // A way is kept when any of these conditions holds after the normalization
+    #[serde(default)]
+    pub keep: Vec<Condition>,
+    #[serde(default)]
+    pub speeds: Vec<SpeedDefinition>,
+    #[serde(default)]
+    pub directions: Vec<DirectionDefinition>,
+    #[serde(default)]
+    pub costs: Vec<CostDefinition>,
+    // Conditions for the properties to mean the same in both directions, so that the contraction
+    // can reverse an edge. Without it, only the profiles without directions are symmetric
+    #[serde(default)]
+    pub symmetric: Option<Vec<Condition>>,
+}
+
+impl ProfileDefinition {
+    // Checks that the rules only refer to declared properties, modes and node properties
+    fn validate(&self) -> Result<(), Error> {
+        let known = |name: &String| self.properties.iter().any(|p| &p.name == name);
+        let check = |name: &String| {
+            if known(name) {
+                Ok(())
+            } else {
+                Err(Error::Profile(format!("unknown property \"{}\"", name)))
+            }
+        };
+        let check_condition = |condition: &Condition| -> Result<(), Error> {
+            check(&condition.property)?;
+            condition.same_as.as_ref().map_or(Ok(()), check)
+        };
+        let check_set = |set: &BTreeMap<String, String>| -> Result<(), Error> {
+            for (property, value) in set {
+                check(property)?;
+                let (source, transforms) = parse_expression(value);
+                if let Some(source) = source.strip_prefix('$') {
+                    if source != "value" && source != "key" {
+                        check(&source.to_string())?;
+                    }
+                }
+                if let Some(transform) = transforms.iter().find(|t| Transform::parse(t).is_none()) {
+                    return Err(Error::Profile(format!(
+                        "unknown transform \"{}\"",
+                        transform.trim()
+                    )));
+                }
+            }
+            Ok(())
+        };
+
+        for rule in &self.rules {
+            // The conditional restrictions are only resolved at a given time by the built-in road profile
+            let conditional = rule.key.ends_with(":conditional")
+                || (rule.key.ends_with('*')
+                    && rule.suffixes.iter().any(|s| s.ends_with("conditional")));
+            if conditional {
+                return Err(Error::Profile(format!(
+                    "conditional restrictions are not supported, rule on \"{}\"",
+                    rule.key
+                )));
+            }
+            rule.when.iter().try_for_each(check_condition)?;
+            check_set(&rule.set)?;
+            check_set(&rule.append)?;
+        }
+        for normalization in &self.normalize {
+            normalization.when.iter().try_for_each(check_condition)?;
+            check_set(&normalization.set)?;
+        }
+        self.keep.iter().try_for_each(check_condition)?;
+        for speed in &self.speeds {
+            if Mode::from_name(&speed.mode).is_none() {
+                return Err(Error::Profile(format!("unknown mode \"{}\"", speed.mode)));
+            }
+            speed.when.iter().try_for_each(check_condition)?;
+            if let Some(maxspeed) = &speed.maxspeed {
+                check(maxspeed)?;
+            }
+        }
+        for cost in &self.costs {
+            if Mode::from_name(&cost.mode).is_none() {
+                return Err(Error::Profile(format!("unknown mode \"{}\"", cost.mode)));
+            }
+            cost.when.iter().try_for_each(check_condition)?;
+        }
+        self.symmetric
+            .iter()
+            .flatten()
+            .try_for_each(check_condition)?;
+        for direction in &self.directions {
+            if Mode::from_name(&direction.mode).is_none() {
+                return Err(Error::Profile(format!(
+                    "unknown mode \"{}\"",
+                    direction.mode
+                )));
+            }
+            direction.forward.iter().try_for_each(check_condition)?;
+            direction.backward.iter().try_for_each(check_condition)?;
+        }
+        match self.nodes.as_deref() {
+            None | Some("railway") => Ok(()),
+            Some(nodes) => Err(Error::Profile(format!("unknown nodes \"{}\"", nodes))),
+        }
+    }
+}
+
+// RuleProfile is a profile defined by tag rules loaded from a TOML or YAML file
+//
+// See profiles/road.toml and profiles/railway.toml for the definitions of the built-in profiles
+#[derive(Clone)]
+pub struct RuleProfile {
+    definition: Arc<ProfileDefinition>,
+    indexes: Arc<HashMap<String, usize>>,
+    values: Vec<String>,
+    rejected: bool,
+}
+
+impl RuleProfile {
+    pub fn new(definition: ProfileDefinition) -> Result<RuleProfile, Error> {
+        definition.validate()?;
+        let indexes = definition
+            .properties
+            .iter()
+            .enumerate()
+            .map(|(i, property)| (property.name.clone(), i))
+            .collect();
+        let values = definition
+            .properties
+            .iter()
+            .map(|property| property.default.clone())
+            .collect();
+        Ok(RuleProfile {
+            definition: Arc::new(definition),
+            indexes: Arc::new(indexes),
+            values,
+            rejected: false,
+        })
+    }
+
+    pub fn from_toml(content: &str) -> Result<RuleProfile, Error> {
+        let definition = toml::from_str(content).map_err(|e| Error::Profile(e.to_string()))?;
+        RuleProfile::new(definition)
+    }
+
+    pub fn from_yaml(content: &str) -> Result<RuleProfile, Error> {
+        let definition =
+            serde_yaml::from_str(content).map_err(|e| Error::Profile(e.to_string()))?;
+        RuleProfile::new(definition)
+    }
+
+    // Reads a .yaml or .yml file as YAML, any other file as TOML
+    pub fn from_file<F: AsRef<Path>>(path: F) -> Result<RuleProfile, Error> {
+        let path = path.as_ref();
+        let content = std::fs::read_to_string(path)?;
+        match path.extension().and_then(|extension| extension.to_str()) {
+            Some("yaml") | Some("yml") => RuleProfile::from_yaml(&content),
+            _ => RuleProfile::from_toml(&content),
+        }
+    }
+
+    // Current value of a property, empty if it has not been set
+    pub fn get(&self, property: &str) -> &str {
+        self.indexes
+            .get(property)
+            .map(|&i| self.values[i].as_str())
+            .unwrap_or_default()
+    }
+
+    fn holds(&self, conditions: &[Condition]) -> bool {
+        conditions.iter().all(|condition| {
+            let value = self.get(&condition.property);
+            condition.is.as_ref().is_none_or(|is| is == value)
+                && condition.not.as_ref().is_none_or(|not| not != value)
+                && condition
+                    .same_as
+                    .as_ref()
+                    .is_none_or(|other| self.get(other) == value)
+        })
+    }
+
+    // Value of an expression of set or append, see Rule
+    fn evaluate(&self, expression: &str, tag_key: &str, tag_value: &str) -> String {
+        let (source, transforms) = parse_expression(expression);
+        let value = match source.strip_prefix('$') {
+            Some("value") => tag_value.to_string(),
+            Some("key") => tag_key.to_string(),
+            Some(source) => self.get(source).to_string(),
+            None => source.to_string(),
+        };
+        transforms
+            .iter()
+            .filter_map(|transform| Transform::parse(transform))
+            .fold(value, |value, transform| transform.apply(&value))
+    }
+
+    fn assign(&mut self, set: &BTreeMap<String, String>, tag_key: &str, tag_value: &str) {
+        for (property, expression) in set {
+            let value = self.evaluate(expression, tag_key, tag_value);
+            let i = self.indexes[property];
+            self.values[i] = match self.definition.properties[i].kind {
+                PropertyKind::Speed => parse_speed(&value)
+                    .map(|speed| speed.to_string())
+                    .unwrap_or_default(),
+                PropertyKind::Length => parse_length(&value)
+                    .map(|length| length.to_string())
+                    .unwrap_or_default(),
+                _ => value,
+            };
+        }
+    }
+
+    fn append(&mut self, append: &BTreeMap<String, String>, tag_key: &str, tag_value: &str) {
+        for (property, expression) in append {
+            let value = self.evaluate(expression, tag_key, tag_value);
+            let i = self.indexes[property];
+            if value.is_empty() {
+                continue;
+            } else if self.values[i].is_empty() {
+                self.values[i] = value;
+            } else {
+                self.values[i] = format!("{};{}", self.values[i], value);
+            }
+        }
+    }
+
+    fn number(&self, property: &str) -> Option<f64> {
+        self.get(property).trim().parse().ok()
+    }
+
+    fn direction(&self, mode: Mode) -> Option<&DirectionDefinition> {
+        self.definition
+            .directions
+            .iter()
+            .find(|direction| direction.mode == mode.name())
+    }
+
+    fn railway_nodes(&self) -> Option<RailwayEdgeProperties> {
+        match self.definition.nodes.as_deref() {
+            Some("railway") => Some(RailwayEdgeProperties::default()),
+            _ => None,
+        }
+    }
+}
+
+fn parse_boolean(value: &str) -> Option<bool> {
+    match value {
+        "yes" | "true" | "1" => Some(true),
+        "no" | "false" | "0" => Some(false),
+        _ => None,
+    }
+}
+
+impl Profile for RuleProfile {
+    // Only read with nodes = "railway"
+    type NodeProperties = RailwayNodeProperties;
+
+    fn update_with_str(&mut self, key: &str, val: &str) {
+        // Not supported, see ProfileDefinition::validate: not even matched by a prefix
+        if key.ends_with(":conditional") {
+            return;
+        }
+        let definition = self.definition.clone();
+        for rule in &definition.rules {
+            let matched_key = match rule.key.strip_suffix('*') {
+                Some(prefix) => key.strip_prefix(prefix).filter(|suffix| {
+                    rule.suffixes.is_empty() || rule.suffixes.iter().any(|s| s == suffix)
+                }),
+                None => (rule.key == key).then_some(key),
+            };
+            let Some(matched_key) = matched_key else {
+                continue;
+            };
+            let matches = (rule.values.is_empty() || rule.values.iter().any(|v| v == val))
+                && !rule.except.iter().any(|v| v == val);
+            if matches && self.holds(&rule.when) {
+                self.rejected |= rule.reject;
+                self.assign(&rule.set, matched_key, val);
+                self.append(&rule.append, matched_key, val);
+            }
+        }
+    }
+
+    fn normalize(&mut self) {
+        let definition = self.definition.clone();
+        for normalization in &definition.normalize {
+            if self.holds(&normalization.when) {
+                self.assign(&normalization.set, "", "");
+            }
+        }
+    }
+
+    fn keep(&self) -> bool {
+        !self.rejected
+            && (self.definition.keep.is_empty()
+                || self
+                    .definition
+                    .keep
+                    .iter()
+                    .any(|condition| self.holds(std::slice::from_ref(condition))))
+    }
+
+    fn columns(&self) -> Vec<String> {
+        self.definition
+            .properties
+            .iter()
+            .filter(|property| property.write)
+            .map(|property| property.name.clone())
+            .collect()
+    }
+
+    fn row(&self) -> Vec<PropertyValue> {
+        self.definition
+            .properties
+            .iter()
+            .zip(&self.values)
+            .filter(|(property, _)| property.write)
+            .map(|(property, value)| match property.kind {
+                PropertyKind::Category => PropertyValue::Category(value.clone()),
+                PropertyKind::Text => PropertyValue::Text(value.clone()),
+                PropertyKind::Number | PropertyKind::Speed | PropertyKind::Length => {
+                    PropertyValue::Number(value.trim().parse().ok())
+                }
+                PropertyKind::Boolean => PropertyValue::Boolean(parse_boolean(value)),
+            })
+            .collect()
+    }
+
+    fn modes(&self) -> Vec<Mode> {
+        let mut modes = Vec::new();
+        for speed in &self.definition.speeds {
+            if let Some(mode) = Mode::from_name(&speed.mode) {
+                if !modes.contains(&mode) {
+                    modes.push(mode);
+                }
+            }
+        }
+        modes
+    }
+
+    fn speed(&self, mode: Mode) -> Option<f64> {
+        let speed = self
+            .definition
+            .speeds
+            .iter()
+            .find(|speed| speed.mode == mode.name() && self.holds(&speed.when))?;
+        let maxspeed = speed
+            .maxspeed
+            .as_ref()
+            .and_then(|property| self.number(property));
+        Some(maxspeed.unwrap_or(speed.speed))
+    }
+
+    fn forward(&self, mode: Mode) -> bool {
+        match self.direction(mode) {
+            Some(direction) => self.holds(&direction.forward),
+            None => self.speed(mode).is_some(),
+        }
+    }
+
+    fn backward(&self, mode: Mode) -> bool {
+        match self.direction(mode) {
+            Some(direction) => self.holds(&direction.backward),
+            None => self.speed(mode).is_some(),
+        }
+    }
+
+    fn cost_factor(&self, mode: Mode) -> f64 {
+        self.definition
+            .costs
+            .iter()
+            .find(|cost| cost.mode == mode.name() && self.holds(&cost.when))
+            .map_or(1., |cost| cost.factor)
+    }
+
+    fn is_symmetric(&self) -> bool {
+        match &self.definition.symmetric {
+            Some(conditions) => self.holds(conditions),
+            // Without direction rules, every mode can use the edge both ways
+            None => self.definition.directions.is_empty(),
+        }
+    }
+
+    fn max_turn_angle(&self) -> Option<f64> {
+        self.definition.max_turn_angle
+    }
+
+    fn geometry_column(&self) -> &str {
+        self.definition
+            .geometry_column
+            .as_deref()
+            .unwrap_or("geometry")
+    }
+
+    fn route_types(&self) -> Vec<String> {
+        self.definition.routes.clone()
+    }
+
+    fn node_properties(&self, tags: &Tags) -> Option<RailwayNodeProperties> {
+        self.railway_nodes()?.node_properties(tags)
+    }
+
+    fn node_columns(&self) -> Vec<String> {
+        match self.railway_nodes() {
+            Some(railway) => railway.node_columns(),
+            None => Vec::new(),
+        }
+    }
+
+    fn node_row(&self, node: &Node) -> Vec<PropertyValue> {
+        match self.railway_nodes() {
+            Some(railway) => railway.node_row(node),
+            None => Vec::new(),
+        }
+    }
+}
+
+// Two ways have the same properties when they were built from the same definition
+impl PartialEq for RuleProfile {
+    fn eq(&self, other: &RuleProfile) -> bool {
+        Arc::ptr_eq(&self.definition, &other.definition)
+            && self.values == other.values
+            && self.rejected == other.rejected
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DEFINITION: &str = r#"
+        routes = ["train"]
+
+        [[properties]]
+        name = "kind"
+
+        [[properties]]
+        name = "gauge"
+        type = "number"
+
+        [[properties]]
+        name = "systems"
+        type = "text"
+
+        [[properties]]
+        name = "maxspeed"
+        type = "speed"
+
+        [[properties]]
+        name = "width"
+        type = "length"
+
+        [[properties]]
+        name = "oneway"
+        default = "no"
+        write = false
+
+        [[rules]]
+        key = "railway"
+        values = ["rail", "tram"]
+        set = { kind = "$value" }
+
+        [[rules]]
+        key = "railway"
+        values = ["abandoned"]
+        reject = true
+
+        [[rules]]
+        key = "gauge"
+        set = { gauge = "$value | replace(standard, 1435) | strip(mm) | first" }
+
+        [[rules]]
+        key = "railway:*"
+        suffixes = ["pzb", "lzb"]
+        except = ["no"]
+        append = { systems = "$key" }
+
+        [[rules]]
+        key = "railway:etcs"
+        append = { systems = "$value | integers | max" }
+
+        [[rules]]
+        key = "maxspeed"
+        set = { maxspeed = "$value" }
+
+        [[rules]]
+        key = "width"
+        set = { width = "$value" }
+
+        [[rules]]
+        key = "oneway"
+        when = [{ property = "kind", is = "tram" }]
+        set = { oneway = "$value" }
+
+        [[normalize]]
+        when = [{ property = "kind", is = "tram" }, { property = "maxspeed", is = "" }]
+        set = { maxspeed = "50" }
+
+        [[normalize]]
+        set = { systems = "$systems | sort" }
+
+        [[keep]]
+        property = "kind"
+        not = ""
+
+        [[speeds]]
+        mode = "car"
+        when = [{ property = "kind", is = "tram" }]
+        speed = 30
+        maxspeed = "maxspeed"
+
+        [[directions]]
+        mode = "car"
+        forward = []
+        backward = [{ property = "oneway", is = "no" }]
+    "#;
+
+    fn profile(tags: &[(&str, &str)]) -> RuleProfile {
+        let mut profile = RuleProfile::from_toml(DEFINITION).unwrap();
+        for (key, value) in tags {
+            profile.update_with_str(key, value);
+        }
+        profile.normalize();
+        profile
+    }
+
+    #[test]
+    fn transforms() {
+        let apply =
+            |transform: &str, value: &str| Transform::parse(transform).unwrap().apply(value);
+        assert_eq!(apply("split", " a; ;b "), "a;b");
+        assert_eq!(apply("first", "15000;25000"), "15000");
+        assert_eq!(apply("max", "1;3;x;2"), "3");
+        assert_eq!(apply("max", "no"), "");
+        assert_eq!(apply("sort", "pzb;etcs;pzb"), "etcs;pzb");
+        assert_eq!(apply("integers", "1;no;2"), "1;2");
+        assert_eq!(
+            apply("replace(standard, 1435)", "standard;1520"),
+            "1435;1520"
+        );
+        assert_eq!(apply("strip(mm)", "1435mm; 1000 mm"), "1435;1000");
+        assert!(Transform::parse("replace(a)").is_none());
+        assert!(Transform::parse("upper").is_none());
+    }
+
+    #[test]
+    fn expressions() {
+        assert_eq!(
+            parse_expression("$value | first"),
+            ("$value", vec![" first"])
+        );
+        assert_eq!(parse_expression("a | b"), ("a | b", Vec::<&str>::new()));
+    }
+
+    #[test]
+    fn rules_set_the_properties() {
+        let profile = profile(&[
+            ("railway", "rail"),
+            ("gauge", "standard;1520"),
+            ("maxspeed", "100 mph"),
+            ("width", "12'6\""),
+        ]);
+        assert!(profile.keep());
+        assert_eq!(profile.get("kind"), "rail");
+        assert_eq!(profile.get("gauge"), "1435");
+        assert!((profile.number("maxspeed").unwrap() - 160.93).abs() < 0.01);
+        assert!((profile.number("width").unwrap() - 3.81).abs() < 0.01);
+        assert_eq!(
+            profile.columns(),
+            ["kind", "gauge", "systems", "maxspeed", "width"]
+        );
+        assert_eq!(profile.row()[1], PropertyValue::Number(Some(1435.)));
+    }
+
+    #[test]
+    fn prefixed_keys_and_appends() {
+        let profile = profile(&[
+            ("railway", "rail"),
+            ("railway:pzb", "yes"),
+            ("railway:lzb", "no"),
+            ("railway:kvb", "yes"),
+            ("railway:etcs", "1;2"),
+        ]);
+        assert_eq!(profile.get("systems"), "2;pzb");
+        assert_eq!(profile.row()[2], PropertyValue::Text("2;pzb".to_string()));
+    }
+
+    #[test]
+    fn keep_and_reject() {
+        assert!(!profile(&[("highway", "primary")]).keep());
+        assert!(!profile(&[("railway", "rail"), ("railway", "abandoned")]).keep());
+        assert!(!profile(&[("railway", "abandoned")]).keep());
+    }
+
+    #[test]
+    fn conditions_speeds_and_directions() {
+        let rail = profile(&[("railway", "rail"), ("oneway", "yes")]);
+        assert_eq!(rail.get("oneway"), "no");
+        assert_eq!(rail.speed(Mode::Car), None);
+
+        let tram = profile(&[("railway", "tram"), ("oneway", "yes")]);
+        assert_eq!(tram.modes(), vec![Mode::Car]);
+        assert_eq!(tram.speed(Mode::Car), Some(50.));
+        assert!(tram.forward(Mode::Car));
+        assert!(!tram.backward(Mode::Car));
+        assert!(!tram.is_symmetric());
+
+        let tram = profile(&[("railway", "tram"), ("maxspeed", "40")]);
+        assert_eq!(tram.speed(Mode::Car), Some(40.));
+        assert!(tram.backward(Mode::Car));
+        assert_eq!(tram.route_types(), vec!["train"]);
+    }
+
+    #[test]
+    fn invalid_definitions() {
+        let unknown_property =
+            DEFINITION.replace("set = { kind = \"$value\" }", "set = { type = \"$value\" }");
+        assert!(matches!(
+            RuleProfile::from_toml(&unknown_property),
+            Err(Error::Profile(message)) if message == "unknown property \"type\""
+        ));
+        let unknown_transform = DEFINITION.replace("| sort", "| upper");
+        assert!(matches!(
+            RuleProfile::from_toml(&unknown_transform),
+            Err(Error::Profile(message)) if message == "unknown transform \"upper\""
+        ));
+        let unknown_mode = DEFINITION.replace("mode = \"car\"", "mode = \"boat\"");
+        assert!(RuleProfile::from_toml(&unknown_mode).is_err());
+    }
+
+    #[test]
+    fn built_in_profile_files() {
+        for file in ["profiles/railway.toml", "profiles/road.toml"] {
+            assert!(RuleProfile::from_file(file).is_ok(), "{} is invalid", file);
+        }
+    }
+}
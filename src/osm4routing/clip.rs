@@ -0,0 +1,305 @@
+use super::error::Error;
+use super::models::coord::Coord;
+use serde_json::Value;
+use std::path::Path;
+
+// What to do with the ways crossing the boundary of the area
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub enum ClipMode {
+    // Keep the ways with at least a part inside the area, including their nodes outside
+    #[default]
+    KeepWhole,
+    // Cut the ways where they cross the boundary, with a new node at the crossing
+    Cut,
+}
+
+// ClipArea is the area to extract, a bounding box or a (multi)polygon
+//
+// The rings are combined with the even-odd rule, so holes and disjoint polygons are supported
+#[derive(Clone, Debug)]
+pub struct ClipArea {
+    rings: Vec<Vec<Coord>>,
+    // Bounding box of the rings (min_lon, min_lat, max_lon, max_lat) to quickly reject far away points
+    envelope: (f64, f64, f64, f64),
+}
+
+fn invalid(message: &str) -> Error {
+    Error::Clip(message.to_string())
+}
+
+// Coordinates of a GeoJSON ring: [[lon, lat], …]
+fn geojson_ring(value: &Value) -> Result<Vec<Coord>, Error> {
+    value
+        .as_array()
+        .ok_or_else(|| invalid("ring is not an array"))?
+        .iter()
+        .map(|position| match position.as_array().map(|p| p.as_slice()) {
+            Some([lon, lat, ..]) => Ok(Coord {
+                lon: lon
+                    .as_f64()
+                    .ok_or_else(|| invalid("longitude is not a number"))?,
+                lat: lat
+                    .as_f64()
+                    .ok_or_else(|| invalid("latitude is not a number"))?,
+            }),
+            _ => Err(invalid("position is not [lon, lat]")),
+        })
+        .collect()
+}
+
+// Rings of every Polygon and MultiPolygon found in a GeoJSON object
+fn geojson_rings(value: &Value, rings: &mut Vec<Vec<Coord>>) -> Result<(), Error> {
+    match value.get("type").and_then(|t| t.as_str()) {
+        Some("FeatureCollection") => {
+            for feature in value["features"].as_array().into_iter().flatten() {
+                geojson_rings(feature, rings)?;
+            }
+        }
+        Some("Feature") => geojson_rings(&value["geometry"], rings)?,
+        Some("Polygon") => {
+            for ring in value["coordinates"].as_array().into_iter().flatten() {
+                rings.push(geojson_ring(ring)?);
+            }
+        }
+        Some("MultiPolygon") => {
+            for polygon in value["coordinates"].as_array().into_iter().flatten() {
+                for ring in polygon.as_array().into_iter().flatten() {
+                    rings.push(geojson_ring(ring)?);
+                }
+            }
+        }
+        _ => {}
+    }
+    Ok(())
+}
+
+// Intersection of the segments a-b and c-d, as the position along a-b (0 at a, 1 at b)
+fn crossing(a: &Coord, b: &Coord, c: &Coord, d: &Coord) -> Option<f64> {
+    let (rx, ry) = (b.lon - a.lon, b.lat - a.lat);
+    let (sx, sy) = (d.lon - c.lon, d.lat - c.lat);
+    let denominator = rx * sy - ry * sx;
+    if denominator == 0. {
+        return None;
+    }
+    let (qx, qy) = (c.lon - a.lon, c.lat - a.lat);
+    let t = (qx * sy - qy * sx) / denominator;
+    let u = (qx * ry - qy * rx) / denominator;
+    ((0. ..=1.).contains(&t) && (0. ..=1.).contains(&u)).then_some(t)
+}
+
+impl ClipArea {
+    pub fn new(rings: Vec<Vec<Coord>>) -> Result<ClipArea, Error> {
+        let rings: Vec<Vec<Coord>> = rings.into_iter().filter(|ring| ring.len() > 2).collect();
+        if rings.is_empty() {
+            return Err(invalid("the area has no polygon"));
+        }
+        let mut envelope = (f64::MAX, f64::MAX, f64::MIN, f64::MIN);
+        for coord in rings.iter().flatten() {
+            envelope.0 = envelope.0.min(coord.lon);
+            envelope.1 = envelope.1.min(coord.lat);
+            envelope.2 = envelope.2.max(coord.lon);
+            envelope.3 = envelope.3.max(coord.lat);
+        }
+        Ok(ClipArea { rings, envelope })
+    }
+
+    pub fn bbox(min_lon: f64, min_lat: f64, max_lon: f64, max_lat: f64) -> Result<ClipArea, Error> {
+        if min_lon >= max_lon || min_lat >= max_lat {
+            return Err(invalid("the bounding box is empty"));
+        }
+        ClipArea::new(vec![vec![
+            Coord {
+                lon: min_lon,
+                lat: min_lat,
+            },
+            Coord {
+                lon: max_lon,
+                lat: min_lat,
+            },
+            Coord {
+                lon: max_lon,
+                lat: max_lat,
+            },
+            Coord {
+                lon: min_lon,
+                lat: max_lat,
+            },
+            Coord {
+                lon: min_lon,
+                lat: min_lat,
+            },
+        ]])
+    }
+
+    // Bounding box written as "min_lon,min_lat,max_lon,max_lat"
+    pub fn from_bbox_str(bbox: &str) -> Result<ClipArea, Error> {
+        let values: Vec<f64> = bbox
+            .split(',')
+            .map(|value| value.trim().parse::<f64>())
+            .collect::<Result<_, _>>()
+            .map_err(|_| invalid("the bounding box must be min_lon,min_lat,max_lon,max_lat"))?;
+        match values[..] {
+            [min_lon, min_lat, max_lon, max_lat] => {
+                ClipArea::bbox(min_lon, min_lat, max_lon, max_lat)
+            }
+            _ => Err(invalid(
+                "the bounding box must be min_lon,min_lat,max_lon,max_lat",
+            )),
+        }
+    }
+
+    // Polygons and MultiPolygons of a GeoJSON geometry, feature or feature collection
+    pub fn from_geojson(content: &str) -> Result<ClipArea, Error> {
+        let value: Value = serde_json::from_str(content).map_err(|e| invalid(&e.to_string()))?;
+        let mut rings = Vec::new();
+        geojson_rings(&value, &mut rings)?;
+        ClipArea::new(rings)
+    }
+
+    // Polygon in the osmosis .poly format
+    // See https://wiki.openstreetmap.org/wiki/Osmosis/Polygon_Filter_File_Format
+    pub fn from_poly(content: &str) -> Result<ClipArea, Error> {
+        let mut rings = Vec::new();
+        let mut ring: Option<Vec<Coord>> = None;
+        // The first line is the name of the polygon
+        for line in content.lines().skip(1).map(str::trim) {
+            if line.is_empty() {
+                continue;
+            }
+            match ring.as_mut() {
+                // Header of a ring ("1", "!2" for a hole) or the final END
+                None if line == "END" => break,
+                None => ring = Some(Vec::new()),
+                Some(_) if line == "END" => rings.extend(ring.take()),
+                Some(coords) => {
+                    let mut values = line.split_whitespace().map(|v| v.parse::<f64>());
+                    match (values.next(), values.next()) {
+                        (Some(Ok(lon)), Some(Ok(lat))) => coords.push(Coord { lon, lat }),
+                        _ => return Err(invalid(&format!("invalid .poly line \"{}\"", line))),
+                    }
+                }
+            }
+        }
+        ClipArea::new(rings)
+    }
+
+    // Reads a .poly file, any other file as GeoJSON
+    pub fn from_file<F: AsRef<Path>>(path: F) -> Result<ClipArea, Error> {
+        let path = path.as_ref();
+        let content = std::fs::read_to_string(path)?;
+        match path.extension().and_then(|extension| extension.to_str()) {
+            Some("poly") => ClipArea::from_poly(&content),
+            _ => ClipArea::from_geojson(&content),
+        }
+    }
+
+    fn segments(&self) -> impl Iterator<Item = (&Coord, &Coord)> {
+        self.rings
+            .iter()
+            .flat_map(|ring| ring.iter().zip(ring.iter().cycle().skip(1)))
+    }
+
+    // Whether the coordinate is inside the bounding box of the area
+    pub fn envelope_contains(&self, coord: &Coord) -> bool {
+        let (min_lon, min_lat, max_lon, max_lat) = self.envelope;
+        coord.lon >= min_lon && coord.lon <= max_lon && coord.lat >= min_lat && coord.lat <= max_lat
+    }
+
+    // Whether the box from min to max intersects the bounding box of the area
+    pub fn envelope_intersects(&self, min: &Coord, max: &Coord) -> bool {
+        let (min_lon, min_lat, max_lon, max_lat) = self.envelope;
+        min.lon <= max_lon && max.lon >= min_lon && min.lat <= max_lat && max.lat >= min_lat
+    }
+
+    pub fn contains(&self, coord: &Coord) -> bool {
+        if !self.envelope_contains(coord) {
+            return false;
+        }
+        // Ray casting towards the east
+        let mut inside = false;
+        for (a, b) in self.segments() {
+            if (a.lat > coord.lat) != (b.lat > coord.lat)
+                && coord.lon < a.lon + (coord.lat - a.lat) / (b.lat - a.lat) * (b.lon - a.lon)
+            {
+                inside = !inside;
+            }
+        }
+        inside
+    }
+
+    // Positions along a-b (0 at a, 1 at b) where the segment crosses the boundary, in order
+    pub fn crossings(&self, a: &Coord, b: &Coord) -> Vec<f64> {
+        let (min_lon, min_lat, max_lon, max_lat) = self.envelope;
+        if a.lon.max(b.lon) < min_lon
+            || a.lon.min(b.lon) > max_lon
+            || a.lat.max(b.lat) < min_lat
+            || a.lat.min(b.lat) > max_lat
+        {
+            return Vec::new();
+        }
+        let mut result: Vec<f64> = self
+            .segments()
+            .filter_map(|(c, d)| crossing(a, b, c, d))
+            .collect();
+        result.sort_by(f64::total_cmp);
+        result.dedup();
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn coord(lon: f64, lat: f64) -> Coord {
+        Coord { lon, lat }
+    }
+
+    #[test]
+    fn bounding_box() {
+        let area = ClipArea::from_bbox_str("2.0, 48.0, 3.0, 49.0").unwrap();
+        assert!(area.contains(&coord(2.5, 48.5)));
+        assert!(!area.contains(&coord(3.5, 48.5)));
+        assert!(ClipArea::from_bbox_str("2,48,3").is_err());
+        assert!(ClipArea::from_bbox_str("3,48,2,49").is_err());
+        assert!(ClipArea::from_bbox_str("a,48,3,49").is_err());
+    }
+
+    #[test]
+    fn polygons_with_holes() {
+        let geojson = r#"{
+            "type": "Feature",
+            "geometry": {
+                "type": "Polygon",
+                "coordinates": [
+                    [[0, 0], [4, 0], [4, 4], [0, 4], [0, 0]],
+                    [[1, 1], [3, 1], [3, 3], [1, 3], [1, 1]]
+                ]
+            }
+        }"#;
+        let area = ClipArea::from_geojson(geojson).unwrap();
+        assert!(area.contains(&coord(0.5, 0.5)));
+        assert!(!area.contains(&coord(2., 2.)));
+        assert!(!area.contains(&coord(5., 2.)));
+        assert!(area.envelope_contains(&coord(2., 2.)));
+
+        let poly = "area\n1\n0 0\n4 0\n4 4\n0 4\nEND\n!2\n1 1\n3 1\n3 3\n1 3\nEND\nEND\n";
+        let area = ClipArea::from_poly(poly).unwrap();
+        assert!(area.contains(&coord(0.5, 0.5)));
+        assert!(!area.contains(&coord(2., 2.)));
+
+        assert!(ClipArea::from_poly("area\n1\n0 zero\nEND\nEND\n").is_err());
+        assert!(ClipArea::from_geojson(r#"{"type": "Point", "coordinates": [0, 0]}"#).is_err());
+    }
+
+    #[test]
+    fn crossings_along_a_segment() {
+        let area = ClipArea::bbox(1., 0., 3., 2.).unwrap();
+        let crossings = area.crossings(&coord(0., 1.), &coord(4., 1.));
+        assert_eq!(crossings.len(), 2);
+        assert!((crossings[0] - 0.25).abs() < 1e-9);
+        assert!((crossings[1] - 0.75).abs() < 1e-9);
+        assert!(area.crossings(&coord(1.5, 1.), &coord(2.5, 1.)).is_empty());
+        assert!(area.crossings(&coord(5., 1.), &coord(6., 1.)).is_empty());
+    }
+}
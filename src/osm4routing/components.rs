@@ -0,0 +1,256 @@
+use super::models::{edge::Edge, node::Node};
+use super::profile::{Mode, Profile};
+use osmpbfreader::objects::NodeId;
+use std::collections::{HashMap, HashSet};
+
+// Size of a connected component
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct ComponentSize {
+    pub edges: usize,
+    // Total length in meters
+    pub length: f64,
+}
+
+// Components are the strongly connected components of the graph
+//
+// They are computed for each mode of the profile as the modes don't use the same edges and directions.
+// Profiles without modes (e.g. railway) are considered as undirected.
+// The components are numbered by decreasing number of edges: 0 is the main component.
+pub struct Components {
+    // component_id, or <mode>_component_id for each mode
+    pub columns: Vec<String>,
+    // Component of the edge for each column, None if the mode can't use the edge
+    // or if the edge links two different components (e.g. a oneway leaving a dead end)
+    pub edges: HashMap<String, Vec<Option<usize>>>,
+    // Size of each component, for each column
+    pub sizes: Vec<Vec<ComponentSize>>,
+}
+
+// Tarjan's algorithm, without recursion to support large graphs
+// Returns the component of every vertex
+fn strongly_connected(adjacency: &[Vec<usize>]) -> Vec<usize> {
+    let n = adjacency.len();
+    let mut index = vec![usize::MAX; n];
+    let mut low = vec![0; n];
+    let mut on_stack = vec![false; n];
+    let mut stack = Vec::new();
+    let mut component = vec![usize::MAX; n];
+    let mut next_index = 0;
+    let mut next_component = 0;
+
+    for root in 0..n {
+        if index[root] != usize::MAX {
+            continue;
+        }
+        index[root] = next_index;
+        low[root] = next_index;
+        next_index += 1;
+        stack.push(root);
+        on_stack[root] = true;
+        let mut work = vec![(root, 0)];
+
+        while let Some((v, i)) = work.last_mut() {
+            let v = *v;
+            if let Some(&w) = adjacency[v].get(*i) {
+                *i += 1;
+                if index[w] == usize::MAX {
+                    index[w] = next_index;
+                    low[w] = next_index;
+                    next_index += 1;
+                    stack.push(w);
+                    on_stack[w] = true;
+                    work.push((w, 0));
+                } else if on_stack[w] {
+                    low[v] = low[v].min(index[w]);
+                }
+                continue;
+            }
+
+            work.pop();
+            if let Some(&(u, _)) = work.last() {
+                low[u] = low[u].min(low[v]);
+            }
+            if low[v] == index[v] {
+                while let Some(w) = stack.pop() {
+                    on_stack[w] = false;
+                    component[w] = next_component;
+                    if w == v {
+                        break;
+                    }
+                }
+                next_component += 1;
+            }
+        }
+    }
+    component
+}
+
+// Component of each edge, renumbered by decreasing size, and the size of each component
+fn edge_components<P: Profile>(
+    edges: &[Edge<P>],
+    mode: Option<Mode>,
+) -> (Vec<Option<usize>>, Vec<ComponentSize>) {
+    let mut vertices: HashMap<NodeId, usize> = HashMap::new();
+    for edge in edges {
+        for node_id in [edge.source, edge.target] {
+            let next = vertices.len();
+            vertices.entry(node_id).or_insert(next);
+        }
+    }
+
+    let mut adjacency = vec![Vec::new(); vertices.len()];
+    for edge in edges {
+        let (source, target) = (vertices[&edge.source], vertices[&edge.target]);
+        let (forward, backward) = match mode {
+            Some(mode) => (
+                edge.properties.forward(mode),
+                edge.properties.backward(mode),
+            ),
+            None => (true, true),
+        };
+        if forward {
+            adjacency[source].push(target);
+        }
+        if backward {
+            adjacency[target].push(source);
+        }
+    }
+    let vertex_components = strongly_connected(&adjacency);
+
+    let components: Vec<Option<usize>> = edges
+        .iter()
+        .map(|edge| {
+            let usable = match mode {
+                Some(mode) => edge.properties.forward(mode) || edge.properties.backward(mode),
+                None => true,
+            };
+            let source = vertex_components[vertices[&edge.source]];
+            let target = vertex_components[vertices[&edge.target]];
+            (usable && source == target).then_some(source)
+        })
+        .collect();
+
+    let component_count = vertex_components.iter().max().map_or(0, |max| max + 1);
+    let mut sizes = vec![ComponentSize::default(); component_count];
+    for (edge, component) in edges.iter().zip(&components) {
+        if let Some(component) = component {
+            sizes[*component].edges += 1;
+            sizes[*component].length += edge.length();
+        }
+    }
+
+    // Components without any edge (e.g. the vertices of a oneway dead end) are forgotten
+    let mut order: Vec<usize> = (0..component_count)
+        .filter(|&c| sizes[c].edges > 0)
+        .collect();
+    order.sort_by(|&a, &b| sizes[b].edges.cmp(&sizes[a].edges).then(a.cmp(&b)));
+    let mut renumber = vec![usize::MAX; component_count];
+    for (new, &old) in order.iter().enumerate() {
+        renumber[old] = new;
+    }
+
+    (
+        components
+            .into_iter()
+            .map(|component| component.map(|c| renumber[c]))
+            .collect(),
+        order.into_iter().map(|c| sizes[c]).collect(),
+    )
+}
+
+impl Components {
+    pub fn compute<P: Profile>(edges: &[Edge<P>], profile: &P) -> Components {
+        let modes = profile.modes();
+        let (columns, modes): (Vec<String>, Vec<Option<Mode>>) = if modes.is_empty() {
+            (vec!["component_id".to_string()], vec![None])
+        } else {
+            modes
+                .into_iter()
+                .map(|mode| (format!("{}_component_id", mode.name()), Some(mode)))
+                .unzip()
+        };
+
+        let mut result = Components {
+            columns,
+            edges: edges
+                .iter()
+                .map(|edge| (edge.id.clone(), Vec::with_capacity(modes.len())))
+                .collect(),
+            sizes: Vec::with_capacity(modes.len()),
+        };
+        for mode in modes {
+            let (components, sizes) = edge_components(edges, mode);
+            for (edge, component) in edges.iter().zip(components) {
+                if let Some(edge_components) = result.edges.get_mut(&edge.id) {
+                    edge_components.push(component);
+                }
+            }
+            result.sizes.push(sizes);
+        }
+        result
+    }
+
+    // Component ids of the edge, in the same order as the columns
+    pub fn row(&self, edge_id: &str) -> Vec<Option<usize>> {
+        self.edges
+            .get(edge_id)
+            .cloned()
+            .unwrap_or_else(|| vec![None; self.columns.len()])
+    }
+
+    // Whether the edge belongs to a component at least as large as the limits for one of the modes
+    // Edges that no mode can use are always kept
+    pub fn is_large<P: Profile>(
+        &self,
+        edge: &Edge<P>,
+        min_edges: Option<usize>,
+        min_length: Option<f64>,
+    ) -> bool {
+        let Some(components) = self.edges.get(&edge.id) else {
+            return true;
+        };
+        let modes = edge.properties.modes();
+        let unusable = !modes.is_empty()
+            && modes
+                .iter()
+                .all(|&mode| !edge.properties.forward(mode) && !edge.properties.backward(mode));
+        if unusable {
+            return true;
+        }
+        components
+            .iter()
+            .zip(&self.sizes)
+            .any(|(component, sizes)| match component {
+                Some(component) => {
+                    let size = sizes[*component];
+                    min_edges.is_none_or(|min| size.edges >= min)
+                        && min_length.is_none_or(|min| size.length >= min)
+                }
+                None => false,
+            })
+    }
+
+    // Removes the edges only belonging to components smaller than the limits,
+    // and the nodes that are no longer used by any edge
+    pub fn drop_small<P: Profile>(
+        &self,
+        nodes: Vec<Node>,
+        edges: Vec<Edge<P>>,
+        min_edges: Option<usize>,
+        min_length: Option<f64>,
+    ) -> (Vec<Node>, Vec<Edge<P>>) {
+        let edges: Vec<Edge<P>> = edges
+            .into_iter()
+            .filter(|edge| self.is_large(edge, min_edges, min_length))
+            .collect();
+        let used: HashSet<NodeId> = edges
+            .iter()
+            .flat_map(|edge| [edge.source, edge.target])
+            .collect();
+        let nodes = nodes
+            .into_iter()
+            .filter(|node| used.contains(&node.id))
+            .collect();
+        (nodes, edges)
+    }
+}
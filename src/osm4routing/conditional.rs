@@ -0,0 +1,403 @@
+use chrono::{Datelike, Duration, NaiveDate, NaiveDateTime, Timelike};
+use std::fmt;
+
+const WEEKDAYS: [&str; 7] = ["Mo", "Tu", "We", "Th", "Fr", "Sa", "Su"];
+const MONTHS: [&str; 12] = [
+    "Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec",
+];
+const DAY: u32 = 24 * 60;
+
+// Rule of an opening_hours value, e.g. "Mo-Fr 07:00-19:00"
+#[derive(Clone, Debug, PartialEq)]
+struct Rule {
+    // Selected months (0 is January) and weekdays (0 is Monday), every day when empty
+    months: Vec<usize>,
+    weekdays: Vec<usize>,
+    // Only selects public or school holidays, that are not known
+    holidays_only: bool,
+    // Open spans in minutes since midnight, the end is after 24:00 when the span goes past midnight
+    spans: Vec<(u32, u32)>,
+    // Closed on the selected days ("off", "closed")
+    off: bool,
+    // Rule separated by a comma: it adds spans to the previous rules instead of replacing them
+    additional: bool,
+}
+
+// Indexes of a list of names and ranges, e.g. "Mo-We,Fr" or "Nov-Feb"
+fn parse_selector(selector: &str, names: &[&str]) -> Option<Vec<usize>> {
+    let index = |name: &str| names.iter().position(|&n| n == name);
+    let mut result = Vec::new();
+    for part in selector.split(',') {
+        match part.split_once('-') {
+            Some((first, last)) => {
+                let (first, last) = (index(first)?, index(last)?);
+                // Ranges can wrap, e.g. Fr-Mo
+                let mut i = first;
+                loop {
+                    result.push(i);
+                    if i == last {
+                        break;
+                    }
+                    i = (i + 1) % names.len();
+                }
+            }
+            None => result.push(index(part)?),
+        }
+    }
+    Some(result)
+}
+
+// Minutes since midnight of "07:30", "24:00" is allowed as an end
+fn parse_time(time: &str) -> Option<u32> {
+    let (hours, minutes) = time.split_once(':')?;
+    let (hours, minutes): (u32, u32) = (hours.parse().ok()?, minutes.parse().ok()?);
+    (hours <= 24 && minutes < 60).then_some(hours * 60 + minutes)
+}
+
+// "07:00-19:00,22:00-02:00"
+fn parse_spans(spans: &str) -> Option<Vec<(u32, u32)>> {
+    spans
+        .split(',')
+        .filter(|span| !span.is_empty())
+        .map(|span| {
+            let (start, end) = span.split_once('-')?;
+            let (start, mut end) = (parse_time(start)?, parse_time(end)?);
+            if end <= start {
+                end += DAY;
+            }
+            Some((start, end))
+        })
+        .collect()
+}
+
+impl Rule {
+    fn parse(text: &str, additional: bool) -> Option<Rule> {
+        let mut rule = Rule {
+            months: Vec::new(),
+            weekdays: Vec::new(),
+            holidays_only: false,
+            spans: Vec::new(),
+            off: false,
+            additional,
+        };
+        for token in text.split_whitespace() {
+            match token {
+                "24/7" | "open" => {}
+                "off" | "closed" => rule.off = true,
+                "PH" | "SH" => rule.holidays_only = rule.weekdays.is_empty(),
+                _ if token.starts_with(|c: char| c.is_ascii_digit()) => {
+                    rule.spans.extend(parse_spans(token)?)
+                }
+                _ => {
+                    // Holidays in a list of weekdays (e.g. "Sa,Su,PH") are ignored
+                    let selector: Vec<&str> = token
+                        .split(',')
+                        .filter(|part| !matches!(*part, "PH" | "SH"))
+                        .collect();
+                    let selector = selector.join(",");
+                    if let Some(weekdays) = parse_selector(&selector, &WEEKDAYS) {
+                        rule.weekdays.extend(weekdays);
+                        rule.holidays_only = false;
+                    } else {
+                        rule.months.extend(parse_selector(&selector, &MONTHS)?);
+                    }
+                }
+            }
+        }
+        Some(rule)
+    }
+
+    fn selects(&self, date: NaiveDate) -> bool {
+        !self.holidays_only
+            && (self.months.is_empty() || self.months.contains(&(date.month0() as usize)))
+            && (self.weekdays.is_empty()
+                || self
+                    .weekdays
+                    .contains(&(date.weekday().num_days_from_monday() as usize)))
+    }
+}
+
+// OpeningHours is a time domain in the opening_hours syntax
+// https://wiki.openstreetmap.org/wiki/Key:opening_hours/specification
+//
+// Only the common subset is supported: weekdays, months, time spans (possibly past midnight),
+// "off", "24/7" and rules separated by ";" or ",". Holidays are not known and never selected.
+#[derive(Clone, Debug, PartialEq)]
+pub struct OpeningHours {
+    source: String,
+    rules: Vec<Rule>,
+}
+
+impl OpeningHours {
+    // None when the value uses an unsupported part of the syntax (e.g. sunrise, week numbers)
+    pub fn parse(value: &str) -> Option<OpeningHours> {
+        let mut rules = Vec::new();
+        for text in value.split(';').map(str::trim).filter(|t| !t.is_empty()) {
+            // A comma followed by a selector starts an additional rule, otherwise it separates a list
+            let mut rule = String::new();
+            let mut additional = false;
+            for piece in text.split(',').map(str::trim) {
+                let selector = piece.starts_with(|c: char| c.is_ascii_alphabetic());
+                if selector && rule.contains(':') {
+                    rules.push(Rule::parse(&rule, additional)?);
+                    rule.clear();
+                    additional = true;
+                }
+                if !rule.is_empty() {
+                    rule.push(',');
+                }
+                rule.push_str(piece);
+            }
+            rules.push(Rule::parse(&rule, additional)?);
+        }
+        if rules.is_empty() {
+            return None;
+        }
+        Some(OpeningHours {
+            source: value.trim().to_string(),
+            rules,
+        })
+    }
+
+    // Open spans of the day, the later rules replace the previous ones for the days they select
+    fn spans(&self, date: NaiveDate) -> Vec<(u32, u32)> {
+        let mut spans = Vec::new();
+        for rule in self.rules.iter().filter(|rule| rule.selects(date)) {
+            if !rule.additional || rule.off {
+                spans.clear();
+            }
+            if !rule.off {
+                if rule.spans.is_empty() {
+                    spans.push((0, DAY));
+                } else {
+                    spans.extend(&rule.spans);
+                }
+            }
+        }
+        spans
+    }
+
+    pub fn is_open(&self, time: &NaiveDateTime) -> bool {
+        let minute = time.hour() * 60 + time.minute();
+        let today = self
+            .spans(time.date())
+            .iter()
+            .any(|&(start, end)| start <= minute && minute < end);
+        let previous_day = time.date() - Duration::days(1);
+        let from_yesterday = self
+            .spans(previous_day)
+            .iter()
+            .any(|&(_, end)| minute + DAY < end);
+        today || from_yesterday
+    }
+}
+
+impl fmt::Display for OpeningHours {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.source)
+    }
+}
+
+// Condition of a conditional restriction
+#[derive(Clone, Debug, PartialEq)]
+pub enum Condition {
+    Time(OpeningHours),
+    // Any other condition (wet, weight>7.5, hgv…), kept as written but never considered to hold
+    Other(String),
+}
+
+impl Condition {
+    pub fn holds(&self, time: &NaiveDateTime) -> bool {
+        match self {
+            Condition::Time(opening_hours) => opening_hours.is_open(time),
+            Condition::Other(_) => false,
+        }
+    }
+}
+
+impl fmt::Display for Condition {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Condition::Time(opening_hours) => write!(f, "{}", opening_hours),
+            Condition::Other(condition) => write!(f, "{}", condition),
+        }
+    }
+}
+
+// Conditional is one restriction of a conditional tag: the key takes the value when every
+// condition holds, e.g. motor_vehicle:conditional=no @ (Mo-Fr 07:00-19:00)
+// https://wiki.openstreetmap.org/wiki/Conditional_restrictions
+#[derive(Clone, Debug, PartialEq)]
+pub struct Conditional {
+    pub key: String,
+    pub value: String,
+    pub conditions: Vec<Condition>,
+}
+
+impl Conditional {
+    pub fn holds(&self, time: &NaiveDateTime) -> bool {
+        self.conditions
+            .iter()
+            .all(|condition| condition.holds(time))
+    }
+}
+
+// key=value @ (condition AND condition)
+impl fmt::Display for Conditional {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let conditions: Vec<String> = self.conditions.iter().map(|c| c.to_string()).collect();
+        write!(
+            f,
+            "{}={} @ ({})",
+            self.key,
+            self.value,
+            conditions.join(" AND ")
+        )
+    }
+}
+
+// Splits on the separator, except inside parentheses
+fn split_outside_parentheses(text: &str, separator: char) -> Vec<&str> {
+    let mut result = Vec::new();
+    let mut depth = 0;
+    let mut start = 0;
+    for (i, c) in text.char_indices() {
+        match c {
+            '(' => depth += 1,
+            ')' => depth -= 1,
+            _ if c == separator && depth == 0 => {
+                result.push(&text[start..i]);
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    result.push(&text[start..]);
+    result
+}
+
+// Restrictions of the value of a <key>:conditional tag, e.g. "no @ (Mo-Fr 07:00-19:00); yes @ wet"
+pub fn parse_conditional(key: &str, value: &str) -> Vec<Conditional> {
+    split_outside_parentheses(value, ';')
+        .into_iter()
+        .filter_map(|restriction| {
+            let (value, conditions) = restriction.split_once('@')?;
+            let conditions = conditions.trim();
+            let conditions = conditions
+                .strip_prefix('(')
+                .and_then(|c| c.strip_suffix(')'))
+                .unwrap_or(conditions);
+            Some(Conditional {
+                key: key.to_string(),
+                value: value.trim().to_string(),
+                conditions: conditions
+                    .split(" AND ")
+                    .map(str::trim)
+                    .map(|condition| match OpeningHours::parse(condition) {
+                        Some(opening_hours) => Condition::Time(opening_hours),
+                        None => Condition::Other(condition.to_string()),
+                    })
+                    .collect(),
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // 2024-06-03 is a Monday
+    fn at(day: u32, hour: u32, minute: u32) -> NaiveDateTime {
+        NaiveDate::from_ymd_opt(2024, 6, day)
+            .unwrap()
+            .and_hms_opt(hour, minute, 0)
+            .unwrap()
+    }
+
+    fn open(value: &str, time: NaiveDateTime) -> bool {
+        OpeningHours::parse(value).unwrap().is_open(&time)
+    }
+
+    #[test]
+    fn weekdays_and_spans() {
+        assert!(open("Mo-Fr 07:00-19:00", at(3, 7, 0)));
+        assert!(!open("Mo-Fr 07:00-19:00", at(3, 19, 0)));
+        assert!(!open("Mo-Fr 07:00-19:00", at(8, 12, 0)));
+        assert!(open("Sa,Su 10:00-12:00,14:00-16:00", at(9, 15, 0)));
+        assert!(!open("Sa,Su 10:00-12:00,14:00-16:00", at(9, 13, 0)));
+        assert!(open("24/7", at(5, 3, 0)));
+    }
+
+    #[test]
+    fn spans_past_midnight() {
+        assert!(open("Fr 22:00-06:00", at(7, 23, 0)));
+        assert!(open("Fr 22:00-06:00", at(8, 5, 59)));
+        assert!(!open("Fr 22:00-06:00", at(8, 6, 0)));
+        assert!(!open("Fr 22:00-06:00", at(6, 23, 0)));
+    }
+
+    #[test]
+    fn later_rules_replace_the_previous_ones() {
+        let value = "Mo-Su 08:00-20:00; Sa off; Su 10:00-12:00";
+        assert!(open(value, at(3, 9, 0)));
+        assert!(!open(value, at(8, 9, 0)));
+        assert!(!open(value, at(9, 9, 0)));
+        assert!(open(value, at(9, 11, 0)));
+        // A comma followed by a selector adds to the previous rule
+        assert!(open("Mo 08:00-10:00, Tu 14:00-16:00", at(4, 15, 0)));
+        assert!(open("Mo 08:00-10:00, Tu 14:00-16:00", at(3, 9, 0)));
+    }
+
+    #[test]
+    fn months_and_holidays() {
+        assert!(open("Jun-Aug 10:00-18:00", at(3, 12, 0)));
+        assert!(!open("Nov-Feb 10:00-18:00", at(3, 12, 0)));
+        // Holidays are never selected
+        assert!(!open("PH 10:00-18:00", at(3, 12, 0)));
+        assert!(open("Mo,PH 10:00-18:00", at(3, 12, 0)));
+    }
+
+    #[test]
+    fn unsupported_syntax() {
+        assert!(OpeningHours::parse("sunrise-sunset").is_none());
+        assert!(OpeningHours::parse("Mo 25:00-26:00").is_none());
+        assert!(OpeningHours::parse("").is_none());
+    }
+
+    #[test]
+    fn conditional_restrictions() {
+        let conditionals = parse_conditional(
+            "maxspeed:conditional",
+            "30 @ (Mo-Fr 07:00-19:00); 50 @ (wet AND 22:00-06:00); none",
+        );
+        assert_eq!(conditionals.len(), 2);
+        assert_eq!(conditionals[0].value, "30");
+        assert!(conditionals[0].holds(&at(3, 8, 0)));
+        assert!(!conditionals[0].holds(&at(9, 8, 0)));
+
+        assert_eq!(conditionals[1].value, "50");
+        assert_eq!(
+            conditionals[1].conditions[0],
+            Condition::Other("wet".to_string())
+        );
+        assert!(matches!(conditionals[1].conditions[1], Condition::Time(_)));
+        // Other conditions never hold
+        assert!(!conditionals[1].holds(&at(3, 23, 0)));
+        assert_eq!(
+            conditionals[1].to_string(),
+            "maxspeed:conditional=50 @ (wet AND 22:00-06:00)"
+        );
+    }
+
+    #[test]
+    fn conditions_with_semicolons() {
+        let conditionals = parse_conditional(
+            "access:conditional",
+            "no @ (Mo 08:00-10:00; We 08:00-10:00)",
+        );
+        assert_eq!(conditionals.len(), 1);
+        assert!(conditionals[0].holds(&at(5, 9, 0)));
+        assert!(!conditionals[0].holds(&at(4, 9, 0)));
+    }
+}
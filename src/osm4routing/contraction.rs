@@ -0,0 +1,123 @@
+use super::models::{edge::Edge, node::Node};
+use super::profile::Profile;
+use super::reader::{nodes_edge_id, EdgeIdScheme};
+use osmpbfreader::objects::NodeId;
+use std::collections::{HashMap, HashSet};
+
+// Merges the chains of edges going through nodes with exactly two incident edges
+//
+// Two edges are merged only when they have equal properties and the same direction through the
+// node, so that directional properties (e.g. car_forward) keep their meaning. Ways drawn head to
+// head or tail to tail are merged too when their properties are symmetric (e.g. railway tracks),
+// one of the edges being reversed.
+// Infrastructure nodes and the protected nodes (e.g. the extremities of the edges used by turn
+// restrictions) are never removed. The merged edge keeps the osm_ids of every way it comes from,
+// and the id of its first edge, or with EdgeIdScheme::Nodes an id naming its new source and target.
+pub fn contract<P: Profile>(
+    nodes: Vec<Node>,
+    edges: Vec<Edge<P>>,
+    protected: &HashSet<NodeId>,
+    edge_ids: EdgeIdScheme,
+) -> (Vec<Node>, Vec<Edge<P>>) {
+    let mut incident: HashMap<NodeId, Vec<usize>> = HashMap::new();
+    for (i, edge) in edges.iter().enumerate() {
+        incident.entry(edge.source).or_default().push(i);
+        incident.entry(edge.target).or_default().push(i);
+    }
+
+    let mut edges: Vec<Option<Edge<P>>> = edges.into_iter().map(Some).collect();
+    let mut removed = HashSet::new();
+    let mut merged = HashSet::new();
+    for node in &nodes {
+        if node.is_infrastructure() || protected.contains(&node.id) {
+            continue;
+        }
+        let (incoming, outgoing) = match incident.get(&node.id).map(|edges| edges.as_slice()) {
+            Some(&[a, b]) if a != b => {
+                let (Some(edge_a), Some(edge_b)) = (&edges[a], &edges[b]) else {
+                    continue;
+                };
+                if edge_a.properties != edge_b.properties {
+                    continue;
+                }
+                let symmetric = edge_a.properties.is_symmetric();
+                match (edge_a.target == node.id, edge_b.source == node.id) {
+                    (true, true) => (a, b),
+                    (false, false) if edge_b.target == node.id && edge_a.source == node.id => {
+                        (b, a)
+                    }
+                    // Head to head: b is reversed to leave the node
+                    (true, false) if symmetric => {
+                        if let Some(edge) = edges[b].as_mut() {
+                            edge.reverse();
+                        }
+                        (a, b)
+                    }
+                    // Tail to tail: a is reversed to reach the node
+                    (false, true) if symmetric => {
+                        if let Some(edge) = edges[a].as_mut() {
+                            edge.reverse();
+                        }
+                        (a, b)
+                    }
+                    _ => continue,
+                }
+            }
+            _ => continue,
+        };
+
+        let Some(next) = edges[outgoing].take() else {
+            continue;
+        };
+        if let Some(edge) = edges[incoming].as_mut() {
+            edge.geometry.extend_from_slice(&next.geometry[1..]);
+            edge.target = next.target;
+            for osm_id in next.osm_ids {
+                if edge.osm_ids.last() != Some(&osm_id) {
+                    edge.osm_ids.push(osm_id);
+                }
+            }
+        }
+        if let Some(target_edges) = incident.get_mut(&next.target) {
+            for i in target_edges.iter_mut().filter(|i| **i == outgoing) {
+                *i = incoming;
+            }
+        }
+        incident.remove(&node.id);
+        removed.insert(node.id);
+        merged.insert(incoming);
+    }
+
+    if edge_ids == EdgeIdScheme::Nodes {
+        rename_merged(&mut edges, &merged);
+    }
+    let nodes = nodes
+        .into_iter()
+        .filter(|node| !removed.contains(&node.id))
+        .collect();
+    (nodes, edges.into_iter().flatten().collect())
+}
+
+// Gives the merged edges the id of their first way and of their new source and target, the other
+// edges keep their id (e.g. the ones used by the restrictions)
+fn rename_merged<P>(edges: &mut [Option<Edge<P>>], merged: &HashSet<usize>) {
+    let mut ids: HashSet<String> = edges
+        .iter()
+        .enumerate()
+        .filter(|(i, _)| !merged.contains(i))
+        .filter_map(|(_, edge)| edge.as_ref().map(|edge| edge.id.clone()))
+        .collect();
+    for (i, edge) in edges.iter_mut().enumerate() {
+        let Some(edge) = edge.as_mut().filter(|_| merged.contains(&i)) else {
+            continue;
+        };
+        let mut occurrence = 0;
+        let mut id = nodes_edge_id(edge.osm_id, edge.source, edge.target, occurrence);
+        while ids.contains(&id) {
+            occurrence += 1;
+            id = nodes_edge_id(edge.osm_id, edge.source, edge.target, occurrence);
+        }
+        ids.insert(id.clone());
+        edge.id = id;
+    }
+}
@@ -0,0 +1,88 @@
+use osmpbfreader::objects::{NodeId, WayId};
+use std::fmt;
+
+// Error is everything that can go wrong while reading OpenStreetMap data or writing the graph
+#[derive(Debug)]
+pub enum Error {
+    Io(std::io::Error),
+    Pbf(osmpbfreader::Error),
+    Xml(String),
+    MissingNode { way_id: WayId, node_id: NodeId },
+    Csv(csv::Error),
+    Json(serde_json::Error),
+    Dataframe(polars::error::PolarsError),
+    // The profile definition file can't be parsed or refers to unknown properties
+    Profile(String),
+    // The clipping area can't be parsed
+    Clip(String),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Io(error) => write!(f, "I/O error: {}", error),
+            Error::Pbf(error) => write!(f, "unable to decode PBF: {}", error),
+            Error::Xml(message) => write!(f, "unable to parse OSM XML: {}", message),
+            Error::MissingNode { way_id, node_id } => write!(
+                f,
+                "way {} refers to node {} that is missing from the extract",
+                way_id.0, node_id.0
+            ),
+            Error::Csv(error) => write!(f, "unable to write CSV: {}", error),
+            Error::Json(error) => write!(f, "unable to write JSON: {}", error),
+            Error::Dataframe(error) => write!(f, "dataframe error: {}", error),
+            Error::Profile(message) => write!(f, "invalid profile: {}", message),
+            Error::Clip(message) => write!(f, "invalid clipping area: {}", message),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Io(error) => Some(error),
+            Error::Pbf(error) => Some(error),
+            Error::MissingNode { .. } => None,
+            Error::Csv(error) => Some(error),
+            Error::Json(error) => Some(error),
+            Error::Dataframe(error) => Some(error),
+            Error::Xml(_) | Error::Profile(_) | Error::Clip(_) => None,
+        }
+    }
+}
+
+impl From<std::io::Error> for Error {
+    fn from(error: std::io::Error) -> Error {
+        Error::Io(error)
+    }
+}
+
+impl From<osmpbfreader::Error> for Error {
+    fn from(error: osmpbfreader::Error) -> Error {
+        Error::Pbf(error)
+    }
+}
+
+impl From<quick_xml::Error> for Error {
+    fn from(error: quick_xml::Error) -> Error {
+        Error::Xml(error.to_string())
+    }
+}
+
+impl From<csv::Error> for Error {
+    fn from(error: csv::Error) -> Error {
+        Error::Csv(error)
+    }
+}
+
+impl From<serde_json::Error> for Error {
+    fn from(error: serde_json::Error) -> Error {
+        Error::Json(error)
+    }
+}
+
+impl From<polars::error::PolarsError> for Error {
+    fn from(error: polars::error::PolarsError) -> Error {
+        Error::Dataframe(error)
+    }
+}
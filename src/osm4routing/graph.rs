@@ -0,0 +1,428 @@
+use super::models::{coord::Coord, edge::Edge, node::Node};
+use super::profile::{Mode, Profile};
+use super::spatial::{EdgeIndex, Snap};
+use osmpbfreader::objects::NodeId;
+use std::cmp::Ordering;
+use std::collections::{BinaryHeap, HashMap};
+
+// GraphArc is an edge travelled in one direction
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct GraphArc {
+    // Index of the edge in Graph::edges
+    pub edge: usize,
+    // Index of the nodes in Graph::nodes
+    pub from: usize,
+    pub to: usize,
+    // Whether the edge is travelled from its source to its target
+    pub forward: bool,
+}
+
+// Edge of a route, in the travel order
+#[derive(Clone, Debug, PartialEq)]
+pub struct RouteEdge {
+    // Index of the edge in Graph::edges
+    pub index: usize,
+    pub edge_id: String,
+    pub forward: bool,
+}
+
+// Route is the result of a shortest path query
+#[derive(Clone, Debug)]
+pub struct Route {
+    pub edges: Vec<RouteEdge>,
+    // Total length in meters
+    pub length: f64,
+    // Total cost: travel time in seconds for a mode, increased by the cost factors of the edges
+    // (see Profile::cost_factor), length in meters without mode
+    pub cost: f64,
+    // Geometry of the edges in the travel order, without repeating the nodes joining them
+    pub geometry: Vec<Coord>,
+}
+
+// Node waiting in the priority queue, the lowest estimate comes first
+#[derive(PartialEq)]
+pub(crate) struct QueueItem {
+    pub(crate) estimate: f64,
+    pub(crate) node: usize,
+}
+
+impl Eq for QueueItem {}
+
+impl Ord for QueueItem {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other
+            .estimate
+            .total_cmp(&self.estimate)
+            .then(other.node.cmp(&self.node))
+    }
+}
+
+impl PartialOrd for QueueItem {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+// Graph is the routable network built from the nodes and edges of the reader
+//
+// Each node knows the arcs leaving it and the arcs reaching it. The arcs a mode can use follow
+// the direction rules of the profile (forward and backward), like the components.
+// Without mode (e.g. for the railway profile), every edge can be used in both directions.
+pub struct Graph<P> {
+    pub nodes: Vec<Node>,
+    pub edges: Vec<Edge<P>>,
+    index: HashMap<NodeId, usize>,
+    outgoing: Vec<Vec<GraphArc>>,
+    incoming: Vec<Vec<GraphArc>>,
+    lengths: Vec<f64>,
+    // Highest speed of each mode on the graph, in km/h, for the heuristic of A*
+    max_speeds: Vec<(Mode, f64)>,
+    spatial: EdgeIndex,
+}
+
+impl<P: Profile> Graph<P> {
+    // The extremities of the edges missing from the nodes are added, at the ends of the geometry
+    pub fn new(nodes: Vec<Node>, edges: Vec<Edge<P>>) -> Graph<P> {
+        let mut graph = Graph {
+            index: nodes
+                .iter()
+                .enumerate()
+                .map(|(i, node)| (node.id, i))
+                .collect(),
+            nodes,
+            outgoing: Vec::new(),
+            incoming: Vec::new(),
+            lengths: edges.iter().map(|edge| edge.length()).collect(),
+            max_speeds: Vec::new(),
+            spatial: EdgeIndex::new(&edges),
+            edges: Vec::new(),
+        };
+        for edge in &edges {
+            let ends = [
+                (edge.source, edge.geometry.first()),
+                (edge.target, edge.geometry.last()),
+            ];
+            for (id, coord) in ends {
+                if !graph.index.contains_key(&id) {
+                    graph.index.insert(id, graph.nodes.len());
+                    graph.nodes.push(Node {
+                        id,
+                        coord: coord.copied().unwrap_or_default(),
+                        ..Default::default()
+                    });
+                }
+            }
+        }
+
+        graph.outgoing = vec![Vec::new(); graph.nodes.len()];
+        graph.incoming = vec![Vec::new(); graph.nodes.len()];
+        for (i, edge) in edges.iter().enumerate() {
+            let (source, target) = (graph.index[&edge.source], graph.index[&edge.target]);
+            for (from, to, forward) in [(source, target, true), (target, source, false)] {
+                let arc = GraphArc {
+                    edge: i,
+                    from,
+                    to,
+                    forward,
+                };
+                graph.outgoing[from].push(arc);
+                graph.incoming[to].push(arc);
+            }
+        }
+
+        for mode in [Mode::Foot, Mode::Bike, Mode::Car] {
+            let max_speed = edges
+                .iter()
+                .filter_map(|edge| edge.properties.speed(mode))
+                .fold(0., f64::max);
+            if max_speed > 0. {
+                graph.max_speeds.push((mode, max_speed));
+            }
+        }
+        graph.edges = edges;
+        graph
+    }
+
+    // Index of the node in Graph::nodes
+    pub fn node_index(&self, id: NodeId) -> Option<usize> {
+        self.index.get(&id).copied()
+    }
+
+    // Length of the edge in meters
+    pub fn length(&self, edge: usize) -> f64 {
+        self.lengths[edge]
+    }
+
+    // Whether the mode can travel along the arc
+    pub fn usable(&self, arc: &GraphArc, mode: Option<Mode>) -> bool {
+        let properties = &self.edges[arc.edge].properties;
+        match mode {
+            Some(mode) if arc.forward => properties.forward(mode),
+            Some(mode) => properties.backward(mode),
+            None => true,
+        }
+    }
+
+    // Cost of the arc: travel time in seconds for a mode, length in meters without mode
+    // None if the mode can't travel along it
+    pub fn cost(&self, arc: &GraphArc, mode: Option<Mode>) -> Option<f64> {
+        if !self.usable(arc, mode) {
+            return None;
+        }
+        match mode {
+            Some(mode) => {
+                let properties = &self.edges[arc.edge].properties;
+                properties.speed(mode).map(|speed| {
+                    self.lengths[arc.edge] / (speed / 3.6) * properties.cost_factor(mode)
+                })
+            }
+            None => Some(self.lengths[arc.edge]),
+        }
+    }
+
+    // Arcs leaving the node that the mode can use
+    pub fn outgoing(
+        &self,
+        node: usize,
+        mode: Option<Mode>,
+    ) -> impl Iterator<Item = &GraphArc> + '_ {
+        self.outgoing[node]
+            .iter()
+            .filter(move |arc| self.usable(arc, mode))
+    }
+
+    // Arcs reaching the node that the mode can use
+    pub fn incoming(
+        &self,
+        node: usize,
+        mode: Option<Mode>,
+    ) -> impl Iterator<Item = &GraphArc> + '_ {
+        self.incoming[node]
+            .iter()
+            .filter(move |arc| self.usable(arc, mode))
+    }
+
+    // The k edges closest to the coordinate that the mode can use, by increasing distance
+    // The search stops at the edges farther than max_distance meters, if any
+    // Used to snap a coordinate on the graph before routing
+    pub fn nearest_edges(
+        &self,
+        coord: &Coord,
+        k: usize,
+        max_distance: Option<f64>,
+        mode: Option<Mode>,
+    ) -> Vec<Snap> {
+        let mut snaps: Vec<Snap> = Vec::with_capacity(k);
+        for (edge, segment) in self.spatial.nearest_segments(coord) {
+            if snaps.len() == k {
+                break;
+            }
+            // The first segment found is the closest of the edge
+            if snaps.iter().any(|snap| snap.edge == edge) {
+                continue;
+            }
+            let (point, offset) = self.spatial.project(&self.edges[edge], segment, coord);
+            let distance = coord.distance(&point);
+            // The segments come by increasing distance, the next ones are farther
+            if max_distance.is_some_and(|max_distance| distance > max_distance) {
+                break;
+            }
+            if !self.accessible(edge, mode) {
+                continue;
+            }
+            let length = self.lengths[edge];
+            snaps.push(Snap {
+                edge,
+                edge_id: self.edges[edge].id.clone(),
+                point,
+                fraction: if length > 0. {
+                    (offset / length).min(1.)
+                } else {
+                    0.
+                },
+                distance,
+            });
+        }
+        snaps.sort_by(|a, b| a.distance.total_cmp(&b.distance));
+        snaps
+    }
+
+    // Whether the mode can use the edge in at least one direction
+    pub fn accessible(&self, edge: usize, mode: Option<Mode>) -> bool {
+        let properties = &self.edges[edge].properties;
+        match mode {
+            Some(mode) => properties.forward(mode) || properties.backward(mode),
+            None => true,
+        }
+    }
+
+    // Shortest path with Dijkstra's algorithm, None if the target can't be reached
+    pub fn dijkstra(&self, source: NodeId, target: NodeId, mode: Option<Mode>) -> Option<Route> {
+        self.shortest_path(source, target, mode, |_| 0.)
+    }
+
+    // Shortest path with A*, guided by the great-circle distance to the target
+    // The result is the same as Dijkstra's, with fewer nodes visited
+    pub fn astar(&self, source: NodeId, target: NodeId, mode: Option<Mode>) -> Option<Route> {
+        let target_coord = self.nodes[self.node_index(target)?].coord;
+        // Meters per unit of cost: the highest speed in m/s, or 1 when the cost is the length
+        let factor = match mode {
+            Some(mode) => {
+                let (_, max_speed) = self.max_speeds.iter().find(|(m, _)| *m == mode)?;
+                max_speed / 3.6
+            }
+            None => 1.,
+        };
+        self.shortest_path(source, target, mode, |node| {
+            self.nodes[node].coord.distance(&target_coord) / factor
+        })
+    }
+
+    fn shortest_path<H>(
+        &self,
+        source: NodeId,
+        target: NodeId,
+        mode: Option<Mode>,
+        heuristic: H,
+    ) -> Option<Route>
+    where
+        H: Fn(usize) -> f64,
+    {
+        let (source, target) = (self.node_index(source)?, self.node_index(target)?);
+        let mut costs = vec![f64::INFINITY; self.nodes.len()];
+        let mut previous: Vec<Option<GraphArc>> = vec![None; self.nodes.len()];
+        let mut queue = BinaryHeap::new();
+        costs[source] = 0.;
+        queue.push(QueueItem {
+            estimate: heuristic(source),
+            node: source,
+        });
+
+        while let Some(QueueItem { estimate, node }) = queue.pop() {
+            if node == target {
+                break;
+            }
+            // Already settled with a lower cost
+            if estimate > costs[node] + heuristic(node) {
+                continue;
+            }
+            for arc in &self.outgoing[node] {
+                let Some(cost) = self.cost(arc, mode) else {
+                    continue;
+                };
+                let cost = costs[node] + cost;
+                if cost < costs[arc.to] {
+                    costs[arc.to] = cost;
+                    previous[arc.to] = Some(*arc);
+                    queue.push(QueueItem {
+                        estimate: cost + heuristic(arc.to),
+                        node: arc.to,
+                    });
+                }
+            }
+        }
+
+        if costs[target].is_infinite() {
+            return None;
+        }
+        let mut arcs = Vec::new();
+        let mut node = target;
+        while let Some(arc) = previous[node] {
+            arcs.push(arc);
+            node = arc.from;
+        }
+        arcs.reverse();
+        let mut route = self.route(&arcs, costs[target]);
+        // The source is the target
+        if route.geometry.is_empty() {
+            route.geometry.push(self.nodes[source].coord);
+        }
+        Some(route)
+    }
+
+    // Cost of reaching every node from the seeds (index of a node and its initial cost)
+    // The search stops at max_cost, the nodes that are not reached within it have an infinite cost
+    pub fn costs_from(
+        &self,
+        seeds: &[(usize, f64)],
+        mode: Option<Mode>,
+        max_cost: f64,
+    ) -> Vec<f64> {
+        let mut costs = vec![f64::INFINITY; self.nodes.len()];
+        let mut queue = BinaryHeap::new();
+        for &(node, cost) in seeds {
+            if cost <= max_cost && cost < costs[node] {
+                costs[node] = cost;
+                queue.push(QueueItem {
+                    estimate: cost,
+                    node,
+                });
+            }
+        }
+
+        while let Some(QueueItem { estimate, node }) = queue.pop() {
+            if estimate > costs[node] {
+                continue;
+            }
+            for arc in &self.outgoing[node] {
+                let Some(cost) = self.cost(arc, mode) else {
+                    continue;
+                };
+                let cost = costs[node] + cost;
+                if cost <= max_cost && cost < costs[arc.to] {
+                    costs[arc.to] = cost;
+                    queue.push(QueueItem {
+                        estimate: cost,
+                        node: arc.to,
+                    });
+                }
+            }
+        }
+        costs
+    }
+
+    // The edge travelled in the given direction
+    pub fn arc(&self, edge: usize, forward: bool) -> GraphArc {
+        let (source, target) = (
+            self.index[&self.edges[edge].source],
+            self.index[&self.edges[edge].target],
+        );
+        let (from, to) = if forward {
+            (source, target)
+        } else {
+            (target, source)
+        };
+        GraphArc {
+            edge,
+            from,
+            to,
+            forward,
+        }
+    }
+
+    // Route along consecutive arcs
+    pub fn route(&self, arcs: &[GraphArc], cost: f64) -> Route {
+        let mut route = Route {
+            edges: Vec::with_capacity(arcs.len()),
+            length: 0.,
+            cost,
+            geometry: Vec::new(),
+        };
+        for arc in arcs {
+            let edge = &self.edges[arc.edge];
+            route.edges.push(RouteEdge {
+                index: arc.edge,
+                edge_id: edge.id.clone(),
+                forward: arc.forward,
+            });
+            route.length += self.lengths[arc.edge];
+            let skip = usize::from(!route.geometry.is_empty());
+            if arc.forward {
+                route.geometry.extend(edge.geometry.iter().skip(skip));
+            } else {
+                route.geometry.extend(edge.geometry.iter().rev().skip(skip));
+            }
+        }
+        route
+    }
+}
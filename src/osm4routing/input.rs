@@ -0,0 +1,228 @@
+use super::error::Error;
+use osmpbfreader::objects::{
+    Node, NodeId, OsmId, OsmObj, Ref, Relation, RelationId, Tags, Way, WayId,
+};
+use quick_xml::events::{BytesStart, Event};
+use std::io::{BufRead, BufReader, Read};
+use std::path::{Path, PathBuf};
+
+// Format of the OpenStreetMap file
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum InputFormat {
+    Pbf,
+    Xml,
+    XmlBz2,
+    XmlGz,
+}
+
+impl InputFormat {
+    // Detects the format from the extension, or from the first bytes of the file when it is not known
+    pub fn detect(path: &Path) -> Result<InputFormat, Error> {
+        let name = path
+            .file_name()
+            .and_then(|name| name.to_str())
+            .unwrap_or_default()
+            .to_lowercase();
+        if name.ends_with(".pbf") {
+            return Ok(InputFormat::Pbf);
+        } else if name.ends_with(".osm") || name.ends_with(".xml") {
+            return Ok(InputFormat::Xml);
+        } else if name.ends_with(".bz2") {
+            return Ok(InputFormat::XmlBz2);
+        } else if name.ends_with(".gz") {
+            return Ok(InputFormat::XmlGz);
+        }
+
+        let mut magic = Vec::with_capacity(8);
+        std::fs::File::open(path)?.take(8).read_to_end(&mut magic)?;
+        let format = match magic.as_slice() {
+            [b'B', b'Z', b'h', ..] => InputFormat::XmlBz2,
+            [0x1f, 0x8b, ..] => InputFormat::XmlGz,
+            // "<?xml" or "<osm", possibly after a byte order mark or spaces
+            bytes
+                if bytes
+                    .iter()
+                    .find(|b| !b.is_ascii_whitespace() && !matches!(b, 0xef | 0xbb | 0xbf))
+                    == Some(&b'<') =>
+            {
+                InputFormat::Xml
+            }
+            _ => InputFormat::Pbf,
+        };
+        Ok(format)
+    }
+}
+
+// OsmFile reads the objects of an OpenStreetMap file, in any of the supported formats
+//
+// The reader goes through the file twice (ways and relations, then nodes), three times with a clip
+// area (nodes near the area first): a PBF file is rewound, an XML file is parsed again.
+pub enum OsmFile {
+    Pbf(osmpbfreader::OsmPbfReader<std::fs::File>),
+    Xml(PathBuf, InputFormat),
+}
+
+impl OsmFile {
+    pub fn open(path: &Path) -> Result<OsmFile, Error> {
+        match InputFormat::detect(path)? {
+            InputFormat::Pbf => Ok(OsmFile::Pbf(osmpbfreader::OsmPbfReader::new(
+                std::fs::File::open(path)?,
+            ))),
+            format => Ok(OsmFile::Xml(path.to_path_buf(), format)),
+        }
+    }
+
+    // Calls f on every object of the file, in the order of the file
+    pub fn for_each<F>(&mut self, mut f: F) -> Result<(), Error>
+    where
+        F: FnMut(OsmObj) -> Result<(), Error>,
+    {
+        match self {
+            OsmFile::Pbf(pbf) => {
+                pbf.rewind()?;
+                // The blobs of the file are decoded in parallel, the objects still come in order
+                for obj in pbf.par_iter() {
+                    f(obj?)?;
+                }
+                Ok(())
+            }
+            OsmFile::Xml(path, format) => {
+                let file = std::fs::File::open(path)?;
+                let reader: Box<dyn BufRead> = match format {
+                    InputFormat::XmlBz2 => {
+                        Box::new(BufReader::new(bzip2::read::MultiBzDecoder::new(file)))
+                    }
+                    InputFormat::XmlGz => {
+                        Box::new(BufReader::new(flate2::read::MultiGzDecoder::new(file)))
+                    }
+                    _ => Box::new(BufReader::new(file)),
+                };
+                read_xml(reader, f)
+            }
+        }
+    }
+}
+
+fn attribute(element: &BytesStart, name: &str) -> Result<Option<String>, Error> {
+    for attribute in element.attributes() {
+        let attribute = attribute.map_err(quick_xml::Error::from)?;
+        if attribute.key.as_ref() == name.as_bytes() {
+            return Ok(Some(attribute.unescape_value()?.into_owned()));
+        }
+    }
+    Ok(None)
+}
+
+fn required<T: std::str::FromStr>(element: &BytesStart, name: &str) -> Result<T, Error> {
+    attribute(element, name)?
+        .and_then(|value| value.parse().ok())
+        .ok_or_else(|| {
+            Error::Xml(format!(
+                "missing or invalid attribute {} on <{}>",
+                name,
+                String::from_utf8_lossy(element.name().as_ref())
+            ))
+        })
+}
+
+fn decimicro(degrees: f64) -> i32 {
+    (degrees * 1e7).round() as i32
+}
+
+// Starts a node, way or relation, and fills the object being read with its children
+fn start_element(element: &BytesStart, current: &mut Option<OsmObj>) -> Result<(), Error> {
+    match element.name().as_ref() {
+        b"node" => {
+            *current = Some(OsmObj::Node(Node {
+                id: NodeId(required(element, "id")?),
+                tags: Tags::new(),
+                decimicro_lat: decimicro(required(element, "lat")?),
+                decimicro_lon: decimicro(required(element, "lon")?),
+            }))
+        }
+        b"way" => {
+            *current = Some(OsmObj::Way(Way {
+                id: WayId(required(element, "id")?),
+                tags: Tags::new(),
+                nodes: Vec::new(),
+            }))
+        }
+        b"relation" => {
+            *current = Some(OsmObj::Relation(Relation {
+                id: RelationId(required(element, "id")?),
+                tags: Tags::new(),
+                refs: Vec::new(),
+            }))
+        }
+        b"tag" => {
+            let tags = match current {
+                Some(OsmObj::Node(node)) => &mut node.tags,
+                Some(OsmObj::Way(way)) => &mut way.tags,
+                Some(OsmObj::Relation(relation)) => &mut relation.tags,
+                None => return Ok(()),
+            };
+            let (key, val): (String, String) = (required(element, "k")?, required(element, "v")?);
+            tags.insert(key.into(), val.into());
+        }
+        b"nd" => {
+            if let Some(OsmObj::Way(way)) = current {
+                way.nodes.push(NodeId(required(element, "ref")?));
+            }
+        }
+        b"member" => {
+            if let Some(OsmObj::Relation(relation)) = current {
+                let id: i64 = required(element, "ref")?;
+                let member = match attribute(element, "type")?.as_deref() {
+                    Some("node") => OsmId::Node(NodeId(id)),
+                    Some("way") => OsmId::Way(WayId(id)),
+                    Some("relation") => OsmId::Relation(RelationId(id)),
+                    _ => return Err(Error::Xml("invalid member type".to_string())),
+                };
+                relation.refs.push(Ref {
+                    member,
+                    role: attribute(element, "role")?.unwrap_or_default().into(),
+                });
+            }
+        }
+        _ => {}
+    }
+    Ok(())
+}
+
+fn is_object(name: &[u8]) -> bool {
+    matches!(name, b"node" | b"way" | b"relation")
+}
+
+// Parses an OSM XML document, see https://wiki.openstreetmap.org/wiki/OSM_XML
+fn read_xml<R, F>(reader: R, mut f: F) -> Result<(), Error>
+where
+    R: BufRead,
+    F: FnMut(OsmObj) -> Result<(), Error>,
+{
+    let mut xml = quick_xml::Reader::from_reader(reader);
+    let mut buffer = Vec::new();
+    let mut current = None;
+    loop {
+        match xml.read_event_into(&mut buffer)? {
+            Event::Start(element) => start_element(&element, &mut current)?,
+            // Objects without children, e.g. <node id="1" lat="0" lon="0"/>
+            Event::Empty(element) => {
+                start_element(&element, &mut current)?;
+                if is_object(element.name().as_ref()) {
+                    if let Some(obj) = current.take() {
+                        f(obj)?;
+                    }
+                }
+            }
+            Event::End(element) if is_object(element.name().as_ref()) => {
+                if let Some(obj) = current.take() {
+                    f(obj)?;
+                }
+            }
+            Event::Eof => break,
+            _ => {}
+        }
+        buffer.clear();
+    }
+    Ok(())
+}
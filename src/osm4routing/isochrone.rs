@@ -0,0 +1,497 @@
+use super::graph::Graph;
+use super::models::coord::Coord;
+use super::profile::{Mode, Profile};
+use osmpbfreader::objects::NodeId;
+use serde_json::{json, Value};
+use std::collections::{BTreeMap, HashSet};
+use std::f64::consts::PI;
+
+// Node reached within the budget
+#[derive(Clone, Debug)]
+pub struct ReachedNode {
+    pub id: NodeId,
+    pub coord: Coord,
+    // Cost to reach the node: seconds for a mode, meters without mode
+    pub cost: f64,
+}
+
+// Part of an edge reached within the budget, between two fractions of its length (0 at the source)
+// An edge reached from both extremities without meeting in the middle has two parts
+#[derive(Clone, Debug)]
+pub struct ReachedEdge {
+    // Index of the edge in Graph::edges
+    pub edge: usize,
+    pub edge_id: String,
+    pub start: f64,
+    pub end: f64,
+    pub geometry: Vec<Coord>,
+}
+
+// Isochrone is everything reachable from a point within a budget
+// The budget is a travel time in seconds for a mode, a distance in meters without mode
+#[derive(Clone, Debug)]
+pub struct Isochrone {
+    pub budget: f64,
+    pub nodes: Vec<ReachedNode>,
+    pub edges: Vec<ReachedEdge>,
+}
+
+impl Isochrone {
+    // Isochrone from a node of the graph, None if the node is not in the graph
+    pub fn from_node<P: Profile>(
+        graph: &Graph<P>,
+        source: NodeId,
+        budget: f64,
+        mode: Option<Mode>,
+    ) -> Option<Isochrone> {
+        let source = graph.node_index(source)?;
+        Some(Isochrone::compute(
+            graph,
+            &[(source, 0.)],
+            Vec::new(),
+            budget,
+            mode,
+        ))
+    }
+
+    // Isochrone from a coordinate, snapped on the closest edge the mode can use
+    // None if the mode can't use any edge
+    pub fn from_coord<P: Profile>(
+        graph: &Graph<P>,
+        coord: &Coord,
+        budget: f64,
+        mode: Option<Mode>,
+    ) -> Option<Isochrone> {
+        let snap = graph
+            .nearest_edges(coord, 1, None, mode)
+            .into_iter()
+            .next()?;
+        let mut seeds = Vec::new();
+        let mut parts = Vec::new();
+        for forward in [true, false] {
+            let arc = graph.arc(snap.edge, forward);
+            let Some(cost) = graph.cost(&arc, mode) else {
+                continue;
+            };
+            // Fraction of the edge between the snapped point and the extremity it travels to
+            let remaining = if forward {
+                1. - snap.fraction
+            } else {
+                snap.fraction
+            };
+            seeds.push((arc.to, remaining * cost));
+            let reach = if cost > 0. { budget / cost } else { 1. };
+            parts.push(if forward {
+                (snap.edge, snap.fraction, (snap.fraction + reach).min(1.))
+            } else {
+                (snap.edge, (snap.fraction - reach).max(0.), snap.fraction)
+            });
+        }
+        Some(Isochrone::compute(graph, &seeds, parts, budget, mode))
+    }
+
+    // Bounded search from the seeds, the parts are the already reached parts of edges
+    fn compute<P: Profile>(
+        graph: &Graph<P>,
+        seeds: &[(usize, f64)],
+        mut parts: Vec<(usize, f64, f64)>,
+        budget: f64,
+        mode: Option<Mode>,
+    ) -> Isochrone {
+        let costs = graph.costs_from(seeds, mode, budget);
+        let mut nodes = Vec::new();
+        for (node, &cost) in costs.iter().enumerate() {
+            if cost > budget {
+                continue;
+            }
+            nodes.push(ReachedNode {
+                id: graph.nodes[node].id,
+                coord: graph.nodes[node].coord,
+                cost,
+            });
+            // The edges leaving a reached node are travelled until the budget is spent
+            for arc in graph.outgoing(node, mode) {
+                let Some(arc_cost) = graph.cost(arc, mode) else {
+                    continue;
+                };
+                let reach = if arc_cost > 0. {
+                    ((budget - cost) / arc_cost).min(1.)
+                } else {
+                    1.
+                };
+                parts.push(if arc.forward {
+                    (arc.edge, 0., reach)
+                } else {
+                    (arc.edge, 1. - reach, 1.)
+                });
+            }
+        }
+
+        // Union of the reached parts of each edge
+        let mut by_edge: BTreeMap<usize, Vec<(f64, f64)>> = BTreeMap::new();
+        for (edge, start, end) in parts {
+            by_edge.entry(edge).or_default().push((start, end));
+        }
+        let mut edges = Vec::new();
+        for (edge, mut intervals) in by_edge {
+            intervals.sort_by(|a, b| a.0.total_cmp(&b.0));
+            let mut merged: Vec<(f64, f64)> = Vec::new();
+            for (start, end) in intervals {
+                match merged.last_mut() {
+                    Some(last) if start <= last.1 => last.1 = last.1.max(end),
+                    _ => merged.push((start, end)),
+                }
+            }
+            for (start, end) in merged {
+                edges.push(ReachedEdge {
+                    edge,
+                    edge_id: graph.edges[edge].id.clone(),
+                    start,
+                    end,
+                    geometry: graph.edges[edge].sub_geometry(start, end),
+                });
+            }
+        }
+
+        Isochrone {
+            budget,
+            nodes,
+            edges,
+        }
+    }
+
+    // Concave hull of the reached network, built from the extremities of the reached parts of edges
+    // with the k-nearest neighbours algorithm (Moreira and Santos, 2007): k is the number of
+    // neighbours considered at each step, a small k follows the network closely (3 at least)
+    // It falls back to the convex hull when no concave hull is found. None with less than 3 points.
+    pub fn concave_hull(&self, k: usize) -> Option<Vec<Coord>> {
+        let points: Vec<Coord> = self
+            .edges
+            .iter()
+            .flat_map(|edge| [edge.geometry.first(), edge.geometry.last()])
+            .flatten()
+            .chain(self.nodes.iter().map(|node| &node.coord))
+            .copied()
+            .collect();
+        let mean_lat = points.iter().map(|c| c.lat).sum::<f64>() / points.len().max(1) as f64;
+        let scale = mean_lat.to_radians().cos();
+        let mut planar: Vec<[f64; 2]> = points.iter().map(|c| [c.lon * scale, c.lat]).collect();
+        planar.sort_by(|a, b| a[0].total_cmp(&b[0]).then(a[1].total_cmp(&b[1])));
+        planar.dedup();
+
+        let hull = concave_hull(&planar, k)?;
+        Some(
+            hull.iter()
+                .map(|&[x, y]| Coord {
+                    lon: x / scale,
+                    lat: y,
+                })
+                .collect(),
+        )
+    }
+
+    // FeatureCollection of the reached parts of edges, and of the concave hull with k neighbours
+    pub fn to_geojson(&self, hull: Option<usize>) -> Value {
+        let mut features: Vec<Value> = self
+            .edges
+            .iter()
+            .map(|edge| {
+                let coordinates: Vec<[f64; 2]> = edge
+                    .geometry
+                    .iter()
+                    .map(|coord| [coord.lon, coord.lat])
+                    .collect();
+                json!({
+                    "type": "Feature",
+                    "geometry": {
+                        "type": "LineString",
+                        "coordinates": coordinates,
+                    },
+                    "properties": {
+                        "id": edge.edge_id,
+                        "start": edge.start,
+                        "end": edge.end,
+                    },
+                })
+            })
+            .collect();
+        if let Some(hull) = hull.and_then(|k| self.concave_hull(k)) {
+            let mut ring: Vec<[f64; 2]> = hull.iter().map(|c| [c.lon, c.lat]).collect();
+            ring.push(ring[0]);
+            features.push(json!({
+                "type": "Feature",
+                "geometry": {
+                    "type": "Polygon",
+                    "coordinates": [ring],
+                },
+                "properties": {
+                    "budget": self.budget,
+                },
+            }));
+        }
+        json!({
+            "type": "FeatureCollection",
+            "features": features,
+        })
+    }
+}
+
+fn cross(o: [f64; 2], a: [f64; 2], b: [f64; 2]) -> f64 {
+    (a[0] - o[0]) * (b[1] - o[1]) - (a[1] - o[1]) * (b[0] - o[0])
+}
+
+// Whether the segments cross each other, touching at an extremity doesn't count
+fn crosses(a: [f64; 2], b: [f64; 2], c: [f64; 2], d: [f64; 2]) -> bool {
+    cross(a, b, c) * cross(a, b, d) < 0. && cross(c, d, a) * cross(c, d, b) < 0.
+}
+
+// Whether the point is inside the polygon or on its boundary
+fn contains(polygon: &[[f64; 2]], point: [f64; 2]) -> bool {
+    let mut inside = false;
+    for i in 0..polygon.len() {
+        let (a, b) = (polygon[i], polygon[(i + 1) % polygon.len()]);
+        // On the boundary, with a tolerance of about a millimeter
+        let length = ((b[0] - a[0]).powi(2) + (b[1] - a[1]).powi(2)).sqrt();
+        let within = (point[0] - a[0]) * (point[0] - b[0]) + (point[1] - a[1]) * (point[1] - b[1]);
+        if within <= 0. && cross(a, b, point).abs() <= 1e-8 * length {
+            return true;
+        }
+        if (a[1] > point[1]) != (b[1] > point[1])
+            && point[0] < a[0] + (point[1] - a[1]) / (b[1] - a[1]) * (b[0] - a[0])
+        {
+            inside = !inside;
+        }
+    }
+    inside
+}
+
+// Andrew's monotone chain, the points are sorted, counterclockwise
+fn convex_hull(points: &[[f64; 2]]) -> Vec<[f64; 2]> {
+    let mut hull: Vec<[f64; 2]> = Vec::new();
+    for pass in [false, true] {
+        let start = hull.len();
+        let iter: Box<dyn Iterator<Item = &[f64; 2]>> = if pass {
+            Box::new(points.iter().rev())
+        } else {
+            Box::new(points.iter())
+        };
+        for &point in iter {
+            while hull.len() >= start + 2
+                && cross(hull[hull.len() - 2], hull[hull.len() - 1], point) <= 0.
+            {
+                hull.pop();
+            }
+            hull.push(point);
+        }
+        hull.pop();
+    }
+    hull
+}
+
+// Most points given to the k-nearest neighbours algorithm, as each attempt is quadratic
+const MAX_HULL_POINTS: usize = 2000;
+// Attempts with a growing k before falling back to the convex hull
+const MAX_HULL_ATTEMPTS: usize = 5;
+
+// Keeps the first point of each cell of a grid, the cells growing until there are at most max
+// points. The points stay sorted.
+fn thin(points: &[[f64; 2]], max: usize) -> Vec<[f64; 2]> {
+    if points.len() <= max {
+        return points.to_vec();
+    }
+    let (min_x, max_x) = (points[0][0], points[points.len() - 1][0]);
+    let (min_y, max_y) = points
+        .iter()
+        .fold((f64::INFINITY, f64::NEG_INFINITY), |(min, max), p| {
+            (min.min(p[1]), max.max(p[1]))
+        });
+    let (width, height) = (max_x - min_x, max_y - min_y);
+    let mut cell = ((width * height) / max as f64)
+        .sqrt()
+        .max(width.max(height) / max as f64);
+    loop {
+        let mut cells = HashSet::new();
+        let thinned: Vec<[f64; 2]> = points
+            .iter()
+            .filter(|p| {
+                let x = ((p[0] - min_x) / cell) as i64;
+                let y = ((p[1] - min_y) / cell) as i64;
+                cells.insert((x, y))
+            })
+            .copied()
+            .collect();
+        if thinned.len() <= max {
+            return thinned;
+        }
+        cell *= 1.5;
+    }
+}
+
+// The points are sorted and without duplicates, the hull is counterclockwise
+// Above MAX_HULL_POINTS, the hull is built from one point per cell of a grid, so a few points can
+// be left just outside of it
+fn concave_hull(points: &[[f64; 2]], k: usize) -> Option<Vec<[f64; 2]>> {
+    concave_hull_attempts(points, k, MAX_HULL_ATTEMPTS)
+}
+
+fn concave_hull_attempts(points: &[[f64; 2]], k: usize, attempts: usize) -> Option<Vec<[f64; 2]>> {
+    if points.len() < 3 {
+        return None;
+    }
+    let thinned = thin(points, MAX_HULL_POINTS);
+    let mut k = k.max(3);
+    for _ in 0..attempts {
+        if k >= thinned.len() {
+            break;
+        }
+        if let Some(hull) = knn_hull(&thinned, k) {
+            return Some(hull);
+        }
+        k += k / 2;
+    }
+    Some(convex_hull(points))
+}
+
+// Counterclockwise angle from a to b, in ]0, 2π]
+fn turn(a: f64, b: f64) -> f64 {
+    let angle = (b - a).rem_euclid(2. * PI);
+    if angle == 0. {
+        2. * PI
+    } else {
+        angle
+    }
+}
+
+fn knn_hull(points: &[[f64; 2]], k: usize) -> Option<Vec<[f64; 2]>> {
+    let first = (0..points.len()).min_by(|&a, &b| {
+        points[a][1]
+            .total_cmp(&points[b][1])
+            .then(points[a][0].total_cmp(&points[b][0]))
+    })?;
+    let mut available = vec![true; points.len()];
+    available[first] = false;
+    let mut hull = vec![first];
+    let mut current = first;
+    // Direction towards the previous point, west for the lowest point as the hull goes counterclockwise
+    let mut back = PI;
+
+    loop {
+        // The first point can close the hull once it is a polygon
+        if hull.len() == 3 {
+            available[first] = true;
+        }
+        let mut candidates: Vec<usize> = (0..points.len()).filter(|&i| available[i]).collect();
+        if candidates.is_empty() {
+            return None;
+        }
+        let distance = |&i: &usize| {
+            (points[i][0] - points[current][0]).powi(2)
+                + (points[i][1] - points[current][1]).powi(2)
+        };
+        if candidates.len() > k {
+            candidates.select_nth_unstable_by(k, |a, b| distance(a).total_cmp(&distance(b)));
+            candidates.truncate(k);
+        }
+        // The rightmost turn first, to keep the other points on the left
+        let angle =
+            |i: usize| (points[i][1] - points[current][1]).atan2(points[i][0] - points[current][0]);
+        candidates.sort_by(|&a, &b| turn(back, angle(a)).total_cmp(&turn(back, angle(b))));
+
+        let next = candidates.into_iter().find(|&candidate| {
+            // The new side must not cross the previous ones, except the ones it touches
+            let last = if candidate == first { 1 } else { 0 };
+            (last..hull.len().saturating_sub(2)).all(|i| {
+                !crosses(
+                    points[current],
+                    points[candidate],
+                    points[hull[i]],
+                    points[hull[i + 1]],
+                )
+            })
+        })?;
+        if next == first {
+            break;
+        }
+        available[next] = false;
+        hull.push(next);
+        back = angle(next) + PI;
+        current = next;
+    }
+
+    let polygon: Vec<[f64; 2]> = hull.iter().map(|&i| points[i]).collect();
+    points
+        .iter()
+        .all(|&point| contains(&polygon, point))
+        .then_some(polygon)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Grid of 60×60 points one unit apart, without its upper right quarter
+    fn l_shape() -> Vec<[f64; 2]> {
+        let mut points = Vec::new();
+        for x in 0..60 {
+            for y in 0..60 {
+                if x < 30 || y < 30 {
+                    points.push([x as f64, y as f64]);
+                }
+            }
+        }
+        points
+    }
+
+    fn distance_to_segment(point: [f64; 2], a: [f64; 2], b: [f64; 2]) -> f64 {
+        let (dx, dy) = (b[0] - a[0], b[1] - a[1]);
+        let t =
+            (((point[0] - a[0]) * dx + (point[1] - a[1]) * dy) / (dx * dx + dy * dy)).clamp(0., 1.);
+        ((point[0] - a[0] - t * dx).powi(2) + (point[1] - a[1] - t * dy).powi(2)).sqrt()
+    }
+
+    fn distance_to_polygon(polygon: &[[f64; 2]], point: [f64; 2]) -> f64 {
+        if contains(polygon, point) {
+            return 0.;
+        }
+        (0..polygon.len())
+            .map(|i| distance_to_segment(point, polygon[i], polygon[(i + 1) % polygon.len()]))
+            .fold(f64::INFINITY, f64::min)
+    }
+
+    #[test]
+    fn thinned_points() {
+        let points = l_shape();
+        assert!(points.len() > MAX_HULL_POINTS);
+        let thinned = thin(&points, MAX_HULL_POINTS);
+        assert!(thinned.len() <= MAX_HULL_POINTS);
+        assert!(thinned.windows(2).all(|pair| pair[0] < pair[1]));
+        assert!(thinned.iter().all(|point| points.contains(point)));
+        assert_eq!(thin(&points[..100], MAX_HULL_POINTS), points[..100]);
+    }
+
+    #[test]
+    fn hull_contains_every_point() {
+        let points = l_shape();
+        let hull = concave_hull(&points, 3).unwrap();
+        // The thinned points are one per cell of about two units
+        for &point in &points {
+            assert!(distance_to_polygon(&hull, point) <= 3., "{point:?}");
+        }
+        // The missing quarter is left out
+        assert!(!contains(&hull, [40., 40.]));
+    }
+
+    #[test]
+    fn convex_hull_when_out_of_attempts() {
+        let points = l_shape();
+        let hull = concave_hull_attempts(&points, 3, 0).unwrap();
+        assert_eq!(hull, convex_hull(&points));
+        assert!(points.iter().all(|&point| contains(&hull, point)));
+        assert!(contains(&hull, [40., 40.]));
+    }
+
+    #[test]
+    fn too_few_points() {
+        assert_eq!(concave_hull(&[[0., 0.], [1., 1.]], 3), None);
+    }
+}
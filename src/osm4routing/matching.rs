@@ -0,0 +1,343 @@
+use super::graph::{Graph, GraphArc, QueueItem, RouteEdge};
+use super::models::coord::Coord;
+use super::profile::{Mode, Profile};
+use super::spatial::Snap;
+use chrono::NaiveDateTime;
+use std::collections::{BinaryHeap, HashMap};
+
+// Position recorded by a GPS
+#[derive(Clone, Copy, Debug)]
+pub struct TracePoint {
+    pub coord: Coord,
+    pub time: NaiveDateTime,
+}
+
+// Parameters of the map matching
+#[derive(Clone, Debug)]
+pub struct MatchOptions {
+    // Standard deviation of the GPS error in meters
+    pub sigma: f64,
+    // Tolerance in meters to the difference between the distance along the network and the
+    // straight distance of two consecutive points, larger for sparse traces
+    pub beta: f64,
+    // Only the edges closer than this many meters to a point are candidates
+    pub radius: f64,
+    // Number of candidate edges of a point
+    pub candidates: usize,
+    // Longest detour in meters between two consecutive points, compared to the straight distance
+    pub max_detour: f64,
+    // Highest possible speed in km/h, the transitions faster than it are impossible
+    pub max_speed: Option<f64>,
+}
+
+impl Default for MatchOptions {
+    fn default() -> MatchOptions {
+        MatchOptions {
+            sigma: 10.,
+            beta: 20.,
+            radius: 50.,
+            candidates: 8,
+            max_detour: 1000.,
+            max_speed: None,
+        }
+    }
+}
+
+// Position of a point of the trace on the network
+#[derive(Clone, Debug)]
+pub struct MatchedPoint {
+    pub snap: Snap,
+    // Whether the edge is travelled from its source to its target
+    pub forward: bool,
+}
+
+// Result of the map matching
+#[derive(Clone, Debug)]
+pub struct MapMatch {
+    // Position of each point of the trace, None when no edge is close enough
+    pub points: Vec<Option<MatchedPoint>>,
+    // Edges travelled, in order
+    // When the trace can't be followed along the network, the matching restarts after a gap
+    pub edges: Vec<RouteEdge>,
+}
+
+// Candidate of a point: a position on an edge and a direction of travel
+struct State {
+    snap: Snap,
+    forward: bool,
+}
+
+// Step of the Viterbi algorithm, for a point with candidates
+struct Layer {
+    point: usize,
+    states: Vec<State>,
+    scores: Vec<f64>,
+    // Best previous state, with the arcs travelled from it (ids, see arc_id)
+    previous: Vec<Option<(usize, Vec<usize>)>>,
+}
+
+fn arc_id(arc: &GraphArc) -> usize {
+    2 * arc.edge + usize::from(arc.forward)
+}
+
+// Direction in radians of a segment, the longitude being scaled by the latitude
+fn bearing(from: &Coord, to: &Coord) -> f64 {
+    let scale = from.lat.to_radians().cos();
+    (to.lat - from.lat).atan2((to.lon - from.lon) * scale)
+}
+
+// Direction of the arc when it reaches its end or leaves its start, from the closest segment
+// of non-zero length as a duplicated node would give the bearing of a null vector
+fn arc_bearing<P>(graph: &Graph<P>, arc: &GraphArc, at_end: bool) -> f64 {
+    let geometry = &graph.edges[arc.edge].geometry;
+    let non_zero =
+        |segment: &&[Coord]| segment[0].lon != segment[1].lon || segment[0].lat != segment[1].lat;
+    let segment = if arc.forward == at_end {
+        geometry.windows(2).rfind(non_zero)
+    } else {
+        geometry.windows(2).find(non_zero)
+    };
+    segment.map_or(0., |segment| match arc.forward {
+        true => bearing(&segment[0], &segment[1]),
+        false => bearing(&segment[1], &segment[0]),
+    })
+}
+
+// Whether a vehicle can go from an arc to the next one, see Profile::max_turn_angle
+fn can_turn<P: Profile>(graph: &Graph<P>, from: &GraphArc, to: &GraphArc) -> bool {
+    match graph.edges[to.edge].properties.max_turn_angle() {
+        Some(max_angle) => {
+            let turn = (arc_bearing(graph, to, false) - arc_bearing(graph, from, true))
+                .to_degrees()
+                .rem_euclid(360.);
+            turn.min(360. - turn) <= max_angle
+        }
+        None => true,
+    }
+}
+
+// Distances from a position on the start arc to the end of the arcs reachable from it,
+// with the previous arc of each one, as long as the arcs start within the max distance
+fn routes_from<P: Profile>(
+    graph: &Graph<P>,
+    start: &GraphArc,
+    offset: f64,
+    mode: Option<Mode>,
+    max_distance: f64,
+) -> HashMap<usize, (f64, Option<usize>)> {
+    let mut reached = HashMap::new();
+    let mut arcs = HashMap::new();
+    let mut queue = BinaryHeap::new();
+    reached.insert(arc_id(start), (offset, None));
+    arcs.insert(arc_id(start), *start);
+    queue.push(QueueItem {
+        estimate: offset,
+        node: arc_id(start),
+    });
+
+    while let Some(QueueItem { estimate, node: id }) = queue.pop() {
+        if estimate > reached[&id].0 || estimate > max_distance {
+            continue;
+        }
+        let arc = arcs[&id];
+        for next in graph.outgoing(arc.to, mode) {
+            if !can_turn(graph, &arc, next) {
+                continue;
+            }
+            let distance = estimate + graph.length(next.edge);
+            let next_id = arc_id(next);
+            if reached
+                .get(&next_id)
+                .is_none_or(|&(best, _)| distance < best)
+            {
+                reached.insert(next_id, (distance, Some(id)));
+                arcs.insert(next_id, *next);
+                queue.push(QueueItem {
+                    estimate: distance,
+                    node: next_id,
+                });
+            }
+        }
+    }
+    reached
+}
+
+// Distance travelled along the edge in the direction, from its start to the fraction
+fn along<P: Profile>(graph: &Graph<P>, edge: usize, forward: bool, fraction: f64) -> f64 {
+    let fraction = if forward { fraction } else { 1. - fraction };
+    fraction * graph.length(edge)
+}
+
+// Search from a candidate, in its direction of travel
+// Turning back at a point is not possible, otherwise the points close to a junction would make
+// small detours on the other edges of the junction
+struct Search {
+    start: GraphArc,
+    // Distance from the start of the arc to the position
+    position: f64,
+    reached: HashMap<usize, (f64, Option<usize>)>,
+}
+
+impl Search {
+    fn new<P: Profile>(
+        graph: &Graph<P>,
+        from: &State,
+        mode: Option<Mode>,
+        max_distance: f64,
+    ) -> Search {
+        let start = graph.arc(from.snap.edge, from.forward);
+        let position = along(graph, start.edge, start.forward, from.snap.fraction);
+        let offset = graph.length(start.edge) - position;
+        Search {
+            start,
+            position,
+            reached: routes_from(graph, &start, offset, mode, max_distance),
+        }
+    }
+
+    // Shortest route to a candidate, with the arcs travelled
+    fn route_to<P: Profile>(&self, graph: &Graph<P>, to: &State) -> Option<(f64, Vec<usize>)> {
+        let end = graph.arc(to.snap.edge, to.forward);
+        let end_position = along(graph, end.edge, end.forward, to.snap.fraction);
+        // Further along the same edge
+        if arc_id(&self.start) == arc_id(&end) {
+            let distance = end_position - self.position;
+            return (distance >= 0.).then(|| (distance, vec![arc_id(&end)]));
+        }
+        let &(distance, _) = self.reached.get(&arc_id(&end))?;
+        let mut arcs = vec![arc_id(&end)];
+        let mut id = arc_id(&end);
+        while let Some(&(_, Some(previous))) = self.reached.get(&id) {
+            arcs.push(previous);
+            id = previous;
+        }
+        arcs.reverse();
+        Some((distance - graph.length(end.edge) + end_position, arcs))
+    }
+}
+
+// Matches a GPS trace on the edges the mode can use with a hidden Markov model
+// (Newson and Krumm, 2009): the candidates of each point are the closest edges, the most likely
+// sequence of candidates is close to the points and the distances along the network between
+// consecutive candidates are close to the straight distances between the points.
+// The turns between edges are limited by Profile::max_turn_angle (e.g. for trains at switches).
+pub fn match_trace<P: Profile>(
+    graph: &Graph<P>,
+    trace: &[TracePoint],
+    mode: Option<Mode>,
+    options: &MatchOptions,
+) -> MapMatch {
+    let mut result = MapMatch {
+        points: vec![None; trace.len()],
+        edges: Vec::new(),
+    };
+    let mut layers: Vec<Layer> = Vec::new();
+    let mut segment_start = 0;
+
+    for (i, point) in trace.iter().enumerate() {
+        let states: Vec<State> = graph
+            .nearest_edges(&point.coord, options.candidates, Some(options.radius), mode)
+            .into_iter()
+            .flat_map(|snap| {
+                let edge = snap.edge;
+                [true, false]
+                    .into_iter()
+                    .filter(move |&forward| graph.usable(&graph.arc(edge, forward), mode))
+                    .map(move |forward| State {
+                        snap: snap.clone(),
+                        forward,
+                    })
+            })
+            .collect();
+        if states.is_empty() {
+            continue;
+        }
+        let emissions: Vec<f64> = states
+            .iter()
+            .map(|state| -0.5 * (state.snap.distance / options.sigma).powi(2))
+            .collect();
+
+        let mut layer = Layer {
+            point: i,
+            scores: vec![f64::NEG_INFINITY; states.len()],
+            previous: (0..states.len()).map(|_| None).collect(),
+            states,
+        };
+        match layers.last() {
+            Some(last) if layers.len() > segment_start => {
+                let previous_point = &trace[last.point];
+                let straight = previous_point.coord.distance(&point.coord);
+                let mut max_distance = straight + options.max_detour;
+                let seconds = (point.time - previous_point.time).num_milliseconds() as f64 / 1000.;
+                if let Some(max_speed) = options.max_speed.filter(|_| seconds > 0.) {
+                    max_distance =
+                        max_distance.min(max_speed / 3.6 * seconds + 2. * options.radius);
+                }
+                for (a, from) in last.states.iter().enumerate() {
+                    if last.scores[a].is_infinite() {
+                        continue;
+                    }
+                    let search = Search::new(graph, from, mode, max_distance);
+                    for (b, to) in layer.states.iter().enumerate() {
+                        let Some((distance, arcs)) = search.route_to(graph, to) else {
+                            continue;
+                        };
+                        if distance > max_distance {
+                            continue;
+                        }
+                        let score = last.scores[a] - (distance - straight).abs() / options.beta
+                            + emissions[b];
+                        if score > layer.scores[b] {
+                            layer.scores[b] = score;
+                            layer.previous[b] = Some((a, arcs));
+                        }
+                    }
+                }
+            }
+            _ => {}
+        }
+        // No candidate can be reached from the previous point: the matching restarts from here
+        if layer.scores.iter().all(|score| score.is_infinite()) {
+            backtrack(graph, &layers[segment_start..], &mut result);
+            segment_start = layers.len();
+            layer.scores = emissions;
+        }
+        layers.push(layer);
+    }
+    backtrack(graph, &layers[segment_start..], &mut result);
+    result
+}
+
+// Follows the most likely states of a segment of the trace backwards, and adds them to the result
+fn backtrack<P: Profile>(graph: &Graph<P>, layers: &[Layer], result: &mut MapMatch) {
+    let Some(last) = layers.last() else {
+        return;
+    };
+    let Some(mut state) =
+        (0..last.states.len()).max_by(|&a, &b| last.scores[a].total_cmp(&last.scores[b]))
+    else {
+        return;
+    };
+    let mut arcs: Vec<usize> = Vec::new();
+    for layer in layers.iter().rev() {
+        let matched = &layer.states[state];
+        result.points[layer.point] = Some(MatchedPoint {
+            snap: matched.snap.clone(),
+            forward: matched.forward,
+        });
+        match &layer.previous[state] {
+            Some((previous, route)) => {
+                arcs.extend(route.iter().rev());
+                state = *previous;
+            }
+            None => arcs.push(arc_id(&graph.arc(matched.snap.edge, matched.forward))),
+        }
+    }
+    arcs.reverse();
+    arcs.dedup();
+    result.edges.extend(arcs.into_iter().map(|id| RouteEdge {
+        index: id / 2,
+        edge_id: graph.edges[id / 2].id.clone(),
+        forward: id % 2 == 1,
+    }));
+}
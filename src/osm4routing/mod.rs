@@ -1,5 +1,19 @@
 pub mod categorize;
+pub mod clip;
+pub mod components;
+pub mod conditional;
+pub mod contraction;
+pub mod error;
+pub mod graph;
+pub mod input;
+pub mod isochrone;
+pub mod matching;
 pub mod models;
-pub mod profile_type;
+pub mod node_store;
+pub mod profile;
 pub mod reader;
+pub mod restrictions;
+pub mod routes;
+pub mod sink;
+pub mod spatial;
 pub mod writers;
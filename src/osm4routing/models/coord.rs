@@ -1,6 +1,22 @@
 // Coord are coordinates in decimal degress WGS84
-#[derive(Copy, Clone, Default)]
+#[derive(Copy, Clone, Debug, Default)]
 pub struct Coord {
     pub lon: f64,
     pub lat: f64,
 }
+
+// Mean earth radius in meters
+const EARTH_RADIUS: f64 = 6_371_008.8;
+
+impl Coord {
+    // Great-circle distance in meters, using the haversine formula
+    pub fn distance(&self, other: &Coord) -> f64 {
+        let d_lat = (other.lat - self.lat).to_radians();
+        let d_lon = (other.lon - self.lon).to_radians();
+        let a = (d_lat / 2.).sin().powi(2)
+            + self.lat.to_radians().cos()
+                * other.lat.to_radians().cos()
+                * (d_lon / 2.).sin().powi(2);
+        2. * EARTH_RADIUS * a.sqrt().asin()
+    }
+}
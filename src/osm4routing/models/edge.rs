@@ -1,21 +1,24 @@
-use super::super::categorize::edge_properties::EdgeProperties;
-// use super::super::categorize::railway::edge_properties::EdgeProperties;
+use super::super::profile::{Mode, Profile};
 use super::coord::Coord;
+use chrono::NaiveDateTime;
 use geohashrust::{BinaryHash, GeoLocation};
 use osmpbfreader::objects::{NodeId, WayId};
 
 // Edge is a topological representation with only two extremities and no geometry
+// The properties are given by the profile used to read the ways
 #[derive(Clone)]
-pub struct Edge {
+pub struct Edge<P> {
     pub id: String,
     pub osm_id: WayId,
+    // Ways the edge comes from, several when edges were merged by the contraction
+    pub osm_ids: Vec<WayId>,
     pub source: NodeId,
     pub target: NodeId,
     pub geometry: Vec<Coord>,
-    pub properties: EdgeProperties,
+    pub properties: P,
 }
 
-impl Edge {
+impl<P> Edge<P> {
     // Geometry in the well known format
     pub fn as_wkt(&self) -> String {
         let coords: Vec<String> = self
@@ -27,6 +30,68 @@ impl Edge {
         format!("LINESTRING({})", coords.as_slice().join(", "))
     }
 
+    // Geometry in the well known binary format (little endian LineString)
+    pub fn as_wkb(&self) -> Vec<u8> {
+        let mut wkb = Vec::with_capacity(9 + 16 * self.geometry.len());
+        wkb.push(1);
+        wkb.extend_from_slice(&2u32.to_le_bytes());
+        wkb.extend_from_slice(&(self.geometry.len() as u32).to_le_bytes());
+        for coord in &self.geometry {
+            wkb.extend_from_slice(&coord.lon.to_le_bytes());
+            wkb.extend_from_slice(&coord.lat.to_le_bytes());
+        }
+        wkb
+    }
+
+    // Swaps the source and the target, the geometry and the ways being reversed too
+    pub fn reverse(&mut self) {
+        std::mem::swap(&mut self.source, &mut self.target);
+        self.geometry.reverse();
+        self.osm_ids.reverse();
+    }
+
+    // Geodesic length in meters
+    pub fn length(&self) -> f64 {
+        self.geometry
+            .windows(2)
+            .map(|pair| pair[0].distance(&pair[1]))
+            .sum()
+    }
+
+    // Part of the geometry between two fractions of the length, 0 being the source and 1 the target
+    pub fn sub_geometry(&self, start: f64, end: f64) -> Vec<Coord> {
+        let length = self.length();
+        let (start, end) = (start * length, end * length);
+        let mut result = Vec::new();
+        let mut travelled = 0.;
+        for pair in self.geometry.windows(2) {
+            let segment = pair[0].distance(&pair[1]);
+            let interpolate = |at: f64| {
+                let t = if segment > 0. {
+                    ((at - travelled) / segment).clamp(0., 1.)
+                } else {
+                    0.
+                };
+                Coord {
+                    lon: pair[0].lon + t * (pair[1].lon - pair[0].lon),
+                    lat: pair[0].lat + t * (pair[1].lat - pair[0].lat),
+                }
+            };
+            if result.is_empty() && start <= travelled + segment {
+                result.push(interpolate(start));
+            }
+            if !result.is_empty() {
+                if end <= travelled + segment {
+                    result.push(interpolate(end));
+                    break;
+                }
+                result.push(pair[1]);
+            }
+            travelled += segment;
+        }
+        result
+    }
+
     pub fn get_geohashes(&self, precision: u8) -> Vec<String> {
         let mut geohashes: Vec<String> = Vec::new();
 
@@ -44,6 +109,27 @@ impl Edge {
         geohashes.sort_unstable();
         geohashes.dedup();
 
-        return geohashes;
+        geohashes
+    }
+}
+
+impl<P: Profile> Edge<P> {
+    // Default travel time in seconds, None if the profile doesn't know the speed of the mode
+    pub fn travel_time(&self, mode: Mode) -> Option<f64> {
+        self.properties
+            .speed(mode)
+            .map(|speed| self.length() / (speed / 3.6))
+    }
+
+    // Whether the mode can use the edge in at least one direction at the local time
+    pub fn accessible_at(&self, mode: Mode, time: &NaiveDateTime) -> bool {
+        self.properties.forward_at(mode, time) || self.properties.backward_at(mode, time)
+    }
+
+    // Travel time in seconds at the local time, e.g. with a conditional maxspeed
+    pub fn travel_time_at(&self, mode: Mode, time: &NaiveDateTime) -> Option<f64> {
+        self.properties
+            .speed_at(mode, time)
+            .map(|speed| self.length() / (speed / 3.6))
     }
 }
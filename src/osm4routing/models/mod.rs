@@ -1,3 +1,5 @@
 pub mod coord;
 pub mod edge;
 pub mod node;
+pub mod restriction;
+pub mod route;
@@ -1,12 +1,29 @@
+use super::super::categorize::node_properties::NodeProps;
 use super::coord::Coord;
 use osmpbfreader::objects::NodeId;
+use std::sync::Arc;
 
 // Node is the OpenStreetMap node
-#[derive(Copy, Clone)]
+#[derive(Clone)]
 pub struct Node {
     pub id: NodeId,
     pub coord: Coord,
     pub uses: i16,
+    // Properties read by Profile::node_properties, None for most nodes
+    pub properties: Option<Arc<dyn NodeProps>>,
+}
+
+impl Node {
+    // A vertex is an extremity of edges: an intersection, the end of a way or an infrastructure
+    pub fn is_vertex(&self) -> bool {
+        self.uses > 1 || self.is_infrastructure()
+    }
+
+    pub fn is_infrastructure(&self) -> bool {
+        self.properties
+            .as_ref()
+            .is_some_and(|properties| properties.is_infrastructure())
+    }
 }
 
 impl Default for Node {
@@ -15,6 +32,7 @@ impl Default for Node {
             id: NodeId(0),
             coord: Default::default(),
             uses: Default::default(),
+            properties: Default::default(),
         }
     }
 }
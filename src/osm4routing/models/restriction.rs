@@ -0,0 +1,38 @@
+use super::super::conditional::Condition;
+use chrono::NaiveDateTime;
+use osmpbfreader::objects::{NodeId, RelationId};
+
+// Restriction is an OpenStreetMap turn restriction resolved onto the edges of the graph
+// https://wiki.openstreetmap.org/wiki/Relation:restriction
+#[derive(Clone, Debug)]
+pub struct Restriction {
+    pub id: RelationId,
+    // Value of the restriction tag, e.g. no_left_turn or only_straight_on
+    pub restriction: String,
+    // Transport mode the restriction is limited to (e.g. hgv for restriction:hgv), empty for every mode
+    pub mode: String,
+    // Conditions of a restriction:conditional, e.g. 22:00-06:00, empty when it always applies
+    pub conditions: Vec<Condition>,
+    // Transport modes that are not concerned by the restriction, e.g. "bicycle;psv"
+    pub except: String,
+    pub from_edge: String,
+    // Set when the restriction goes through a node
+    pub via_node: Option<NodeId>,
+    // Set when the restriction goes through one or more ways
+    pub via_edges: Vec<String>,
+    pub to_edge: String,
+}
+
+impl Restriction {
+    // Mandatory restrictions (only_*) forbid every other turn than the one described
+    pub fn is_mandatory(&self) -> bool {
+        self.restriction.starts_with("only_")
+    }
+
+    // Whether the restriction applies at the local time, always true without conditions
+    pub fn holds(&self, time: &NaiveDateTime) -> bool {
+        self.conditions
+            .iter()
+            .all(|condition| condition.holds(time))
+    }
+}
@@ -0,0 +1,14 @@
+use osmpbfreader::objects::RelationId;
+
+// RouteMember tells that an edge is part of an OpenStreetMap route relation, e.g. a railway line
+// https://wiki.openstreetmap.org/wiki/Relation:route
+#[derive(Clone, Debug)]
+pub struct RouteMember {
+    pub edge_id: String,
+    pub relation_id: RelationId,
+    // Value of the route tag, e.g. train, tracks or tram
+    pub route: String,
+    pub reference: String,
+    pub name: String,
+    pub operator: String,
+}
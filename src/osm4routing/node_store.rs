@@ -0,0 +1,288 @@
+use super::categorize::node_properties::NodeProps;
+use super::models::{coord::Coord, node::Node};
+use osmpbfreader::objects::NodeId;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+// Coordinates of a node that was not found in the extract
+const UNKNOWN: (i32, i32) = (i32::MIN, i32::MIN);
+
+fn decimicro(degrees: f64) -> i32 {
+    (degrees * 1e7).round() as i32
+}
+
+// NodeStore holds the nodes used by the ways, in a compact form for large extracts
+//
+// The ids are sorted to be found by binary search, and the coordinates are kept in decimicro degrees
+// like in the PBF files: a node costs 18 bytes instead of a HashMap entry holding a Node.
+// Only the few nodes with properties (e.g. railway infrastructure) have an entry in a map.
+#[derive(Default)]
+pub struct NodeStore {
+    ids: Vec<NodeId>,
+    coords: Vec<(i32, i32)>,
+    uses: Vec<i16>,
+    properties: HashMap<NodeId, Arc<dyn NodeProps>>,
+}
+
+impl NodeStore {
+    // Store for the given nodes, they are unknown until they are inserted
+    pub fn new(mut ids: Vec<NodeId>) -> NodeStore {
+        ids.sort_unstable();
+        ids.dedup();
+        ids.shrink_to_fit();
+        NodeStore {
+            coords: vec![UNKNOWN; ids.len()],
+            uses: vec![0; ids.len()],
+            ids,
+            properties: HashMap::new(),
+        }
+    }
+
+    fn index(&self, id: NodeId) -> Option<usize> {
+        self.ids.binary_search(&id).ok()
+    }
+
+    fn known_index(&self, id: NodeId) -> Option<usize> {
+        self.index(id).filter(|&i| self.coords[i] != UNKNOWN)
+    }
+
+    // Sets the coordinates of a node, ignored if the node was not given to new
+    pub fn insert(
+        &mut self,
+        id: NodeId,
+        decimicro_lon: i32,
+        decimicro_lat: i32,
+        properties: Option<Arc<dyn NodeProps>>,
+    ) {
+        if let Some(i) = self.index(id) {
+            self.coords[i] = (decimicro_lon, decimicro_lat);
+            if let Some(properties) = properties {
+                self.properties.insert(id, properties);
+            }
+        }
+    }
+
+    // Whether the node is used by a way, even if its coordinates are unknown
+    pub fn is_wanted(&self, id: NodeId) -> bool {
+        self.index(id).is_some()
+    }
+
+    pub fn contains(&self, id: NodeId) -> bool {
+        self.known_index(id).is_some()
+    }
+
+    pub fn coord(&self, id: NodeId) -> Option<Coord> {
+        self.known_index(id).map(|i| {
+            let (lon, lat) = self.coords[i];
+            Coord {
+                lon: lon as f64 * 1e-7,
+                lat: lat as f64 * 1e-7,
+            }
+        })
+    }
+
+    pub fn add_uses(&mut self, id: NodeId, uses: i16) {
+        if let Some(i) = self.known_index(id) {
+            self.uses[i] += uses;
+        }
+    }
+
+    pub fn is_vertex(&self, id: NodeId) -> bool {
+        match self.known_index(id) {
+            Some(i) => {
+                self.uses[i] > 1
+                    || self
+                        .properties
+                        .get(&id)
+                        .is_some_and(|properties| properties.is_infrastructure())
+            }
+            None => false,
+        }
+    }
+
+    // Known nodes with their coordinates
+    pub fn iter(&self) -> impl Iterator<Item = (NodeId, Coord)> + '_ {
+        self.ids
+            .iter()
+            .filter_map(|&id| Some((id, self.coord(id)?)))
+    }
+
+    // Forgets the nodes for which the predicate is false
+    pub fn retain<F: Fn(NodeId) -> bool>(&mut self, keep: F) {
+        for (i, &id) in self.ids.iter().enumerate() {
+            if !keep(id) {
+                self.coords[i] = UNKNOWN;
+                self.properties.remove(&id);
+            }
+        }
+    }
+
+    // Adds new nodes, e.g. created where the ways are cut
+    pub fn extend(&mut self, nodes: Vec<Node>) {
+        for node in nodes {
+            self.ids.push(node.id);
+            self.coords
+                .push((decimicro(node.coord.lon), decimicro(node.coord.lat)));
+            self.uses.push(node.uses);
+            if let Some(properties) = node.properties {
+                self.properties.insert(node.id, properties);
+            }
+        }
+        let mut order: Vec<usize> = (0..self.ids.len()).collect();
+        order.sort_by_key(|&i| self.ids[i]);
+        self.ids = order.iter().map(|&i| self.ids[i]).collect();
+        self.coords = order.iter().map(|&i| self.coords[i]).collect();
+        self.uses = order.iter().map(|&i| self.uses[i]).collect();
+    }
+
+    // The vertices of the graph, see Node::is_vertex
+    pub fn vertices(&self) -> impl Iterator<Item = Node> + '_ {
+        self.ids
+            .iter()
+            .enumerate()
+            .filter(|&(_, &id)| self.is_vertex(id))
+            .map(|(i, &id)| Node {
+                id,
+                coord: self.coord(id).unwrap_or_default(),
+                uses: self.uses[i],
+                properties: self.properties.get(&id).cloned(),
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::osm4routing::categorize::railway::node_properties::{
+        NodeProperties as RailwayNodeProperties, RailwayNodeType,
+    };
+
+    // The nodes as they were stored before the NodeStore: a map of the nodes used by the ways
+    struct HashMapStore {
+        nodes: HashMap<NodeId, Node>,
+    }
+
+    impl HashMapStore {
+        fn vertices(&self) -> Vec<(NodeId, i32, i32, i16, bool)> {
+            let mut vertices: Vec<_> = self
+                .nodes
+                .values()
+                .filter(|node| node.is_vertex())
+                .map(|node| {
+                    (
+                        node.id,
+                        decimicro(node.coord.lon),
+                        decimicro(node.coord.lat),
+                        node.uses,
+                        node.is_infrastructure(),
+                    )
+                })
+                .collect();
+            vertices.sort_by_key(|vertex| vertex.0);
+            vertices
+        }
+    }
+
+    // Deterministic pseudo-random numbers
+    struct Lcg(u64);
+
+    impl Lcg {
+        fn next(&mut self, max: u64) -> u64 {
+            self.0 = self
+                .0
+                .wrapping_mul(6364136223846793005)
+                .wrapping_add(1442695040888963407);
+            (self.0 >> 33) % max
+        }
+    }
+
+    #[test]
+    fn same_results_as_a_hash_map() {
+        let mut random = Lcg(42);
+        // Ways of random nodes, some of them missing from the file or used several times
+        let ways: Vec<Vec<NodeId>> = (0..300)
+            .map(|_| {
+                (0..2 + random.next(8))
+                    .map(|_| NodeId(random.next(2000) as i64 - 100))
+                    .collect()
+            })
+            .collect();
+        let file: Vec<(NodeId, i32, i32, bool)> = (-100..1800)
+            .map(|id| {
+                (
+                    NodeId(id),
+                    random.next(3_600_000_000) as i32 - 1_800_000_000,
+                    random.next(1_800_000_000) as i32 - 900_000_000,
+                    random.next(50) == 0,
+                )
+            })
+            .collect();
+        let properties = |infrastructure: bool| {
+            infrastructure.then(|| {
+                Arc::new(RailwayNodeProperties {
+                    railway_type: Some(RailwayNodeType::Signal),
+                    ..Default::default()
+                }) as Arc<dyn NodeProps>
+            })
+        };
+
+        let mut store = NodeStore::new(ways.iter().flatten().copied().collect());
+        let mut map = HashMapStore {
+            nodes: HashMap::new(),
+        };
+        let wanted: std::collections::HashSet<NodeId> = ways.iter().flatten().copied().collect();
+        for &(id, lon, lat, infrastructure) in &file {
+            store.insert(id, lon, lat, properties(infrastructure));
+            if wanted.contains(&id) {
+                map.nodes.insert(
+                    id,
+                    Node {
+                        id,
+                        coord: Coord {
+                            lon: lon as f64 * 1e-7,
+                            lat: lat as f64 * 1e-7,
+                        },
+                        uses: 0,
+                        properties: properties(infrastructure),
+                    },
+                );
+            }
+        }
+        for way in &ways {
+            for (i, id) in way.iter().enumerate() {
+                let uses = if i == 0 || i == way.len() - 1 { 2 } else { 1 };
+                store.add_uses(*id, uses);
+                if let Some(node) = map.nodes.get_mut(id) {
+                    node.uses += uses;
+                }
+            }
+        }
+
+        for id in (-150..2100).map(NodeId) {
+            assert_eq!(store.contains(id), map.nodes.contains_key(&id));
+            assert_eq!(store.is_wanted(id), wanted.contains(&id));
+            let expected = map.nodes.get(&id).map(|node| node.coord);
+            let coord = store.coord(id);
+            assert_eq!(coord.is_some(), expected.is_some());
+            if let (Some(coord), Some(expected)) = (coord, expected) {
+                assert!((coord.lon - expected.lon).abs() < 1e-12);
+                assert!((coord.lat - expected.lat).abs() < 1e-12);
+            }
+            let is_vertex = map.nodes.get(&id).is_some_and(|node| node.is_vertex());
+            assert_eq!(store.is_vertex(id), is_vertex);
+        }
+        let vertices: Vec<_> = store
+            .vertices()
+            .map(|node| {
+                (
+                    node.id,
+                    decimicro(node.coord.lon),
+                    decimicro(node.coord.lat),
+                    node.uses,
+                    node.is_infrastructure(),
+                )
+            })
+            .collect();
+        assert_eq!(vertices, map.vertices());
+    }
+}
@@ -0,0 +1,263 @@
+use super::categorize::node_properties::NodeProps;
+use super::models::node::Node;
+use chrono::NaiveDateTime;
+use osmpbfreader::objects::Tags;
+use serde::Serialize;
+use std::fmt;
+
+// Mode of transportation that can travel on the edges
+#[derive(Clone, Copy, Debug, Serialize, PartialEq)]
+pub enum Mode {
+    Foot,
+    Bike,
+    Car,
+}
+
+impl Mode {
+    pub fn name(self) -> &'static str {
+        match self {
+            Mode::Foot => "foot",
+            Mode::Bike => "bike",
+            Mode::Car => "car",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Mode> {
+        match name {
+            "foot" => Some(Mode::Foot),
+            "bike" => Some(Mode::Bike),
+            "car" => Some(Mode::Car),
+            _ => None,
+        }
+    }
+}
+
+// Value of a property column, typed so that every writer can store it natively
+#[derive(Clone, Debug, PartialEq)]
+pub enum PropertyValue {
+    // Value among a small set, e.g. an enum, stored as a categorical when possible
+    Category(String),
+    Text(String),
+    Number(Option<f64>),
+    Boolean(Option<bool>),
+}
+
+impl PropertyValue {
+    // Category named after the variant of an enum
+    pub fn category<T: fmt::Debug>(value: T) -> PropertyValue {
+        PropertyValue::Category(format!("{:?}", value))
+    }
+}
+
+// Missing numbers and booleans are written as empty strings
+impl fmt::Display for PropertyValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PropertyValue::Category(value) | PropertyValue::Text(value) => write!(f, "{}", value),
+            PropertyValue::Number(Some(value)) => write!(f, "{}", value),
+            PropertyValue::Boolean(Some(value)) => write!(f, "{}", value),
+            PropertyValue::Number(None) | PropertyValue::Boolean(None) => Ok(()),
+        }
+    }
+}
+
+const MPH: f64 = 1.609344;
+
+// Implicit speed limits in km/h by country and zone, e.g. maxspeed=FR:urban
+// See https://wiki.openstreetmap.org/wiki/Default_speed_limits
+const ZONE_SPEEDS: [(&str, &str, f64); 44] = [
+    ("AT", "urban", 50.),
+    ("AT", "rural", 100.),
+    ("AT", "motorway", 130.),
+    ("BE", "urban", 50.),
+    ("BE", "rural", 70.),
+    ("BE", "motorway", 120.),
+    ("CH", "urban", 50.),
+    ("CH", "rural", 80.),
+    ("CH", "trunk", 100.),
+    ("CH", "motorway", 120.),
+    ("CZ", "urban", 50.),
+    ("CZ", "rural", 90.),
+    ("CZ", "motorway", 130.),
+    ("DE", "urban", 50.),
+    ("DE", "rural", 100.),
+    ("DE", "living_street", 7.),
+    ("DE", "bicycle_road", 30.),
+    ("DK", "urban", 50.),
+    ("DK", "rural", 80.),
+    ("DK", "motorway", 130.),
+    ("ES", "urban", 50.),
+    ("ES", "rural", 90.),
+    ("ES", "motorway", 120.),
+    ("FI", "urban", 50.),
+    ("FI", "rural", 80.),
+    ("FI", "motorway", 120.),
+    ("FR", "urban", 50.),
+    ("FR", "rural", 80.),
+    ("FR", "trunk", 110.),
+    ("FR", "motorway", 130.),
+    ("FR", "living_street", 20.),
+    ("GB", "nsl_single", 60. * MPH),
+    ("GB", "nsl_dual", 70. * MPH),
+    ("GB", "motorway", 70. * MPH),
+    ("IT", "urban", 50.),
+    ("IT", "rural", 90.),
+    ("IT", "trunk", 110.),
+    ("IT", "motorway", 130.),
+    ("NL", "urban", 50.),
+    ("NL", "rural", 80.),
+    ("NL", "motorway", 100.),
+    ("PL", "urban", 50.),
+    ("PL", "rural", 90.),
+    ("PL", "motorway", 140.),
+];
+
+// Length in meters (e.g. a width) from "3.5", "3.5 m" or a value in feet and inches like 12'6"
+pub fn parse_length(value: &str) -> Option<f64> {
+    let value = value.trim();
+    if let Some((feet, inches)) = value.split_once('\'') {
+        let feet = feet.trim().parse::<f64>().ok()?;
+        let inches = inches.trim().trim_end_matches('"').trim();
+        let inches = if inches.is_empty() {
+            0.
+        } else {
+            inches.parse::<f64>().ok()?
+        };
+        return Some(feet * 0.3048 + inches * 0.0254);
+    }
+    value.trim_end_matches('m').trim().parse().ok()
+}
+
+// Speed in km/h from a maxspeed like value: "50", "30 mph", "50;30" (the first one is used),
+// "walk", or an implicit limit like "FR:urban" or "DE:zone30"
+// None when the value is unknown or means no limit ("none", "signals", "DE:motorway")
+pub fn parse_speed(value: &str) -> Option<f64> {
+    let value = value.split(';').next()?.trim();
+    if value == "walk" {
+        return Some(7.);
+    }
+    if let Some((country, zone)) = value.split_once(':') {
+        // "zone30", "zone:30"
+        if let Some(limit) = zone.strip_prefix("zone") {
+            return limit.trim_start_matches(':').parse::<f64>().ok();
+        }
+        return ZONE_SPEEDS
+            .iter()
+            .find(|&&(c, z, _)| c == country && z == zone)
+            .map(|&(_, _, speed)| speed);
+    }
+    match value.strip_suffix("mph") {
+        Some(mph) => mph.trim().parse::<f64>().ok().map(|speed| speed * MPH),
+        None => value.trim_end_matches("km/h").trim().parse::<f64>().ok(),
+    }
+}
+
+// Profile decides which ways are part of the graph and which properties their edges carry
+//
+// The reader clones the profile for every way and updates it with the tags of the way,
+// the result becomes the properties of the edges built from that way.
+// Edges with equal properties can be merged by the contraction.
+// Implementing this trait is enough to extract a new kind of network (waterway, bus…)
+pub trait Profile: Clone + PartialEq {
+    // Properties of the nodes, NoNodeProperties for the profiles that don't read the nodes
+    type NodeProperties: NodeProps + Default + 'static;
+
+    // Reads one tag of the way
+    fn update_with_str(&mut self, key: &str, val: &str);
+
+    fn update(&mut self, key_string: String, val_string: String) {
+        self.update_with_str(key_string.as_str(), val_string.as_str());
+    }
+
+    // Fills the unknown values once every tag has been read
+    fn normalize(&mut self) {}
+
+    // Whether the way is part of the graph
+    fn keep(&self) -> bool;
+
+    // Names of the property columns written for each edge
+    fn columns(&self) -> Vec<String>;
+
+    // Property values, in the same order as the columns
+    fn row(&self) -> Vec<PropertyValue>;
+
+    // Modes for which a travel time is computed
+    fn modes(&self) -> Vec<Mode> {
+        Vec::new()
+    }
+
+    // Speed in km/h of the mode, None if the mode can't use the edge
+    fn speed(&self, _mode: Mode) -> Option<f64> {
+        None
+    }
+
+    // Whether the mode can travel from the source to the target of the edge
+    fn forward(&self, mode: Mode) -> bool {
+        self.speed(mode).is_some()
+    }
+
+    // Whether the mode can travel from the target to the source of the edge
+    fn backward(&self, mode: Mode) -> bool {
+        self.speed(mode).is_some()
+    }
+
+    // Factor of the travel time of the mode in the routing costs, above 1 to avoid the edge
+    // e.g. the roads only open to reach a destination along them
+    fn cost_factor(&self, _mode: Mode) -> f64 {
+        1.
+    }
+
+    // Same as speed, forward and backward at a local time, for the profiles with time-dependent
+    // restrictions (e.g. access:conditional=no @ (Mo-Fr 07:00-19:00))
+    fn speed_at(&self, mode: Mode, _time: &NaiveDateTime) -> Option<f64> {
+        self.speed(mode)
+    }
+
+    fn forward_at(&self, mode: Mode, _time: &NaiveDateTime) -> bool {
+        self.forward(mode)
+    }
+
+    fn backward_at(&self, mode: Mode, _time: &NaiveDateTime) -> bool {
+        self.backward(mode)
+    }
+
+    // Name of the WKT geometry column of edges.csv
+    fn geometry_column(&self) -> &str {
+        "geometry"
+    }
+
+    // Whether the properties mean the same in both directions of the edge, so that the edge
+    // can be reversed, e.g. by the contraction to merge two ways drawn head to head
+    fn is_symmetric(&self) -> bool {
+        false
+    }
+
+    // Sharpest turn in degrees between two consecutive edges, None without limit
+    // Used by the map matching, e.g. a train can't go from a branch of a switch to the other
+    fn max_turn_angle(&self) -> Option<f64> {
+        None
+    }
+
+    // Values of the route tag of the relations written as routes of the edges, e.g. train
+    fn route_types(&self) -> Vec<String> {
+        Vec::new()
+    }
+
+    // Properties of a node of the network read from its tags, None when it has none
+    fn node_properties(&self, _tags: &Tags) -> Option<Self::NodeProperties> {
+        None
+    }
+
+    // Names of the property columns written for each node
+    fn node_columns(&self) -> Vec<String> {
+        Vec::new()
+    }
+
+    // Node property values, in the same order as the node columns
+    fn node_row(&self, node: &Node) -> Vec<PropertyValue> {
+        match &node.properties {
+            Some(properties) => properties.row(),
+            None => Self::NodeProperties::default().row(),
+        }
+    }
+}
@@ -1,35 +1,153 @@
-use crate::ProfileType;
-
-use super::categorize::edge_properties::EdgeProperties;
-use super::categorize::railway::edge_properties::EdgeProperties as RailwayEdgeProperties;
-use super::categorize::road::edge_properties::EdgeProperties as RoadEdgeProperties;
-use super::models::{coord::Coord, edge::Edge, node::Node};
+use super::categorize::node_properties::NodeProps;
+use super::clip::{ClipArea, ClipMode};
+use super::contraction;
+use super::error::Error;
+use super::input::OsmFile;
+use super::models::{
+    coord::Coord, edge::Edge, node::Node, restriction::Restriction, route::RouteMember,
+};
+use super::node_store::NodeStore;
+use super::profile::Profile;
+use super::restrictions::{self, RestrictionRelation};
+use super::routes::{RouteRelation, Routes};
+use super::sink::{EdgeSink, NodeSink};
 use osmpbfreader::objects::{NodeId, WayId};
 use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
 
 // Way as represented in OpenStreetMap
-struct Way {
+struct Way<P> {
     id: WayId,
     nodes: Vec<NodeId>,
-    properties: EdgeProperties,
+    properties: P,
+}
+
+// Placeholder in the nodes of a way cut by the clipping, where the way leaves the area between two
+// new nodes. Like a missing node, it is never known so the way is split there
+const OUTSIDE: NodeId = NodeId(i64::MIN);
+
+// Whether the bounding box of the nodes intersects the bounding box of the area
+// A way with none of its nodes in the file is kept, the missing nodes policy handles it
+fn near_area(area: &ClipArea, node_coords: &[(NodeId, i32, i32)], nodes: &[NodeId]) -> bool {
+    let mut envelope: Option<(i32, i32, i32, i32)> = None;
+    for id in nodes {
+        let Ok(index) = node_coords.binary_search_by_key(id, |&(id, _, _)| id) else {
+            continue;
+        };
+        let (_, lon, lat) = node_coords[index];
+        envelope = Some(match envelope {
+            None => (lon, lat, lon, lat),
+            Some((min_lon, min_lat, max_lon, max_lat)) => (
+                min_lon.min(lon),
+                min_lat.min(lat),
+                max_lon.max(lon),
+                max_lat.max(lat),
+            ),
+        });
+    }
+    let Some((min_lon, min_lat, max_lon, max_lat)) = envelope else {
+        return true;
+    };
+    let degrees = |decimicro: i32| decimicro as f64 * 1e-7;
+    area.envelope_intersects(
+        &Coord {
+            lon: degrees(min_lon),
+            lat: degrees(min_lat),
+        },
+        &Coord {
+            lon: degrees(max_lon),
+            lat: degrees(max_lat),
+        },
+    )
+}
+
+// Remove the known nodes that are isolated between unknown nodes, as they can't be part of any edge
+fn remove_isolated_nodes(way_nodes: &mut Vec<NodeId>, nodes: &NodeStore) {
+    let known: Vec<bool> = way_nodes.iter().map(|&n| nodes.contains(n)).collect();
+    let mut i = 0;
+    way_nodes.retain(|_| {
+        let keep = !known[i] || (i > 0 && known[i - 1]) || (i + 1 < known.len() && known[i + 1]);
+        i += 1;
+        keep
+    });
+}
+
+// Relations resolved onto the edges of the graph
+#[derive(Default)]
+pub struct EdgeRelations {
+    pub restrictions: Vec<Restriction>,
+    pub routes: Vec<RouteMember>,
+}
+
+// What to do with ways that refer to nodes missing from the extract
+// This is common with extracts clipped by a bounding box or a polygon
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub enum MissingNodePolicy {
+    // Stop the reading with Error::MissingNode
+    #[default]
+    Abort,
+    // Ignore the whole way
+    DropWay,
+    // Cut the way at the missing nodes and keep the pieces with at least two nodes
+    TruncateWay,
+}
+
+// How the ids of the edges are built from their way
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub enum EdgeIdScheme {
+    // Way id and position of the edge in the way (e.g. 42-0, 42-1…)
+    // A new junction in the middle of the way shifts the ids of the following edges
+    #[default]
+    Index,
+    // Way id and ids of the source and target nodes (e.g. 42-1001-1005)
+    // The id stays the same across extractions as long as the edge is unchanged
+    Nodes,
 }
 
-pub struct Reader {
-    nodes: HashMap<NodeId, Node>,
-    ways: Vec<Way>,
-    nodes_to_keep: HashSet<NodeId>,
+// Id of an edge with EdgeIdScheme::Nodes, occurrence counting the previous edges of the way going
+// from the same source to the same target (e.g. a loop), the first one having no suffix
+pub fn nodes_edge_id(way_id: WayId, source: NodeId, target: NodeId, occurrence: usize) -> String {
+    match occurrence {
+        0 => format!("{}-{}-{}", way_id.0, source.0, target.0),
+        n => format!("{}-{}-{}-{}", way_id.0, source.0, target.0, n),
+    }
+}
+
+pub struct Reader<P> {
+    nodes: NodeStore,
+    ways: Vec<Way<P>>,
+    nodes_to_keep: Vec<NodeId>,
     forbidden: HashMap<String, HashSet<String>>,
-    profile_type: ProfileType,
+    profile: P,
+    missing_nodes: MissingNodePolicy,
+    restrictions: Vec<RestrictionRelation>,
+    routes: Vec<RouteRelation>,
+    contract: bool,
+    clip: Option<(ClipArea, ClipMode)>,
+    // With a clip area, coordinates in decimicro degrees of every node of the file sorted by id:
+    // only the ways whose bounding box intersects the bounding box of the area are read
+    node_coords: Option<Vec<(NodeId, i32, i32)>>,
+    // Lowest node id of the file, the new nodes of the clipping get lower ids
+    lowest_node_id: i64,
+    edge_ids: EdgeIdScheme,
 }
 
-impl Reader {
-    pub fn new(profile: ProfileType) -> Reader {
+impl<P: Profile> Reader<P> {
+    pub fn new(profile: P) -> Reader<P> {
         Reader {
-            nodes: HashMap::new(),
+            nodes: NodeStore::default(),
             ways: Vec::new(),
-            nodes_to_keep: HashSet::new(),
+            nodes_to_keep: Vec::new(),
             forbidden: HashMap::new(),
-            profile_type: profile,
+            profile,
+            missing_nodes: MissingNodePolicy::default(),
+            restrictions: Vec::new(),
+            routes: Vec::new(),
+            contract: false,
+            clip: None,
+            node_coords: None,
+            lowest_node_id: 0,
+            edge_ids: EdgeIdScheme::default(),
         }
     }
 
@@ -41,169 +159,432 @@ impl Reader {
         self
     }
 
+    pub fn missing_nodes(mut self, policy: MissingNodePolicy) -> Self {
+        self.missing_nodes = policy;
+        self
+    }
+
+    // Merge the edges through nodes with only two incident edges, see contraction::contract
+    pub fn contract(mut self, contract: bool) -> Self {
+        self.contract = contract;
+        self
+    }
+
+    // Only keep the ways inside the area, see ClipMode for the ways crossing its boundary
+    // The ways without any node in the bounding box of the area are skipped while reading
+    pub fn clip(mut self, area: ClipArea, mode: ClipMode) -> Self {
+        self.clip = Some((area, mode));
+        self
+    }
+
+    pub fn edge_ids(mut self, scheme: EdgeIdScheme) -> Self {
+        self.edge_ids = scheme;
+        self
+    }
+
+    fn handle_missing_nodes(&mut self) -> Result<(), Error> {
+        match self.missing_nodes {
+            MissingNodePolicy::Abort => {
+                for way in &self.ways {
+                    if let Some(node_id) = way.nodes.iter().find(|&&n| !self.nodes.contains(n)) {
+                        return Err(Error::MissingNode {
+                            way_id: way.id,
+                            node_id: *node_id,
+                        });
+                    }
+                }
+            }
+            MissingNodePolicy::DropWay => {
+                let nodes = &self.nodes;
+                self.ways
+                    .retain(|way| way.nodes.iter().all(|&n| nodes.contains(n)));
+            }
+            MissingNodePolicy::TruncateWay => {
+                let nodes = &self.nodes;
+                for way in &mut self.ways {
+                    remove_isolated_nodes(&mut way.nodes, nodes);
+                }
+                self.ways
+                    .retain(|way| way.nodes.iter().any(|&n| nodes.contains(n)));
+            }
+        }
+        Ok(())
+    }
+
+    fn clip_ways(&mut self) {
+        let Some((area, mode)) = self.clip.take() else {
+            return;
+        };
+        let inside: HashSet<NodeId> = self
+            .nodes
+            .iter()
+            .filter(|(_, coord)| area.contains(coord))
+            .map(|(id, _)| id)
+            .collect();
+
+        match mode {
+            ClipMode::KeepWhole => {
+                let nodes = &self.nodes;
+                self.ways.retain(|way| {
+                    way.nodes.iter().any(|n| inside.contains(n))
+                        || way.nodes.windows(2).any(|pair| {
+                            match (nodes.coord(pair[0]), nodes.coord(pair[1])) {
+                                (Some(a), Some(b)) => !area.crossings(&a, &b).is_empty(),
+                                _ => false,
+                            }
+                        })
+                });
+            }
+            ClipMode::Cut => {
+                // The new nodes at the boundary have negative ids below every id of the file, as
+                // files saved by the OpenStreetMap editors can already have negative ids
+                let mut next_id = self.lowest_node_id.min(0) - 1;
+                let mut boundary_nodes = Vec::new();
+                for way in &mut self.ways {
+                    let mut way_nodes = Vec::with_capacity(way.nodes.len());
+                    for (i, &node_id) in way.nodes.iter().enumerate() {
+                        let segment = i
+                            .checked_sub(1)
+                            .and_then(|previous| self.nodes.coord(way.nodes[previous]))
+                            .zip(self.nodes.coord(node_id));
+                        if let Some((a, b)) = segment {
+                            let mut outside = !inside.contains(&way.nodes[i - 1]);
+                            let mut after_boundary = false;
+                            for t in area.crossings(&a, &b) {
+                                if outside && after_boundary {
+                                    way_nodes.push(OUTSIDE);
+                                }
+                                let id = NodeId(next_id);
+                                next_id -= 1;
+                                boundary_nodes.push(Node {
+                                    id,
+                                    coord: Coord {
+                                        lon: a.lon + t * (b.lon - a.lon),
+                                        lat: a.lat + t * (b.lat - a.lat),
+                                    },
+                                    ..Default::default()
+                                });
+                                way_nodes.push(id);
+                                outside = !outside;
+                                after_boundary = true;
+                            }
+                        }
+                        way_nodes.push(node_id);
+                    }
+                    way.nodes = way_nodes;
+                }
+
+                self.nodes.retain(|id| inside.contains(&id));
+                self.nodes.extend(boundary_nodes);
+                let nodes = &self.nodes;
+                for way in &mut self.ways {
+                    remove_isolated_nodes(&mut way.nodes, nodes);
+                }
+                self.ways
+                    .retain(|way| way.nodes.iter().any(|&n| nodes.contains(n)));
+            }
+        }
+
+        // Forget the nodes of the ways outside of the area
+        let used: HashSet<NodeId> = self.ways.iter().flat_map(|way| way.nodes.clone()).collect();
+        self.nodes.retain(|id| used.contains(&id));
+    }
+
     fn count_nodes_uses(&mut self) {
         for way in &self.ways {
             for (i, node_id) in way.nodes.iter().enumerate() {
-                if let Some(node) = self.nodes.get_mut(node_id) {
-                    // Count double extremities nodes
-                    if i == 0 || i == way.nodes.len() - 1 {
-                        node.uses += 2;
-                    } else {
-                        node.uses += 1;
-                    }
-                } else {
-                    panic!("Missing node, id: {:?}", node_id)
-                }
+                // Nodes next to a missing node are the extremities of a truncated way
+                let extremity = i == 0
+                    || i == way.nodes.len() - 1
+                    || !self.nodes.contains(way.nodes[i - 1])
+                    || !self.nodes.contains(way.nodes[i + 1]);
+                // Count double extremities nodes
+                self.nodes.add_uses(*node_id, if extremity { 2 } else { 1 });
             }
         }
     }
 
-    fn split_way(&self, way: &Way) -> Vec<Edge> {
+    fn split_way(&self, way: &Way<P>) -> Vec<Edge<P>> {
         let mut result = Vec::new();
 
-        let mut source = NodeId(0);
+        let mut source = None;
         let mut geometry = Vec::new();
-        for (i, &node_id) in way.nodes.iter().enumerate() {
-            let node = self.nodes[&node_id];
-            if i == 0 {
-                source = node_id;
-                geometry.push(node.coord);
-            } else {
-                geometry.push(node.coord);
-
-                if node.uses > 1 {
+        let mut occurrences: HashMap<(NodeId, NodeId), usize> = HashMap::new();
+        for &node_id in way.nodes.iter() {
+            let Some(coord) = self.nodes.coord(node_id) else {
+                // The way was truncated: start over at the next known node
+                source = None;
+                geometry = Vec::new();
+                continue;
+            };
+            geometry.push(coord);
+
+            match source {
+                None => source = Some(node_id),
+                Some(source_id) if self.nodes.is_vertex(node_id) => {
+                    let id = match self.edge_ids {
+                        EdgeIdScheme::Index => format!("{}-{}", way.id.0, result.len()),
+                        EdgeIdScheme::Nodes => {
+                            let occurrence = occurrences.entry((source_id, node_id)).or_default();
+                            *occurrence += 1;
+                            nodes_edge_id(way.id, source_id, node_id, *occurrence - 1)
+                        }
+                    };
                     result.push(Edge {
-                        id: format!("{}-{}", way.id.0, result.len()),
+                        id,
                         osm_id: way.id,
-                        source,
+                        osm_ids: vec![way.id],
+                        source: source_id,
                         target: node_id,
                         geometry,
                         properties: way.properties.clone(),
                     });
 
-                    source = node_id;
-                    geometry = vec![node.coord];
+                    source = Some(node_id);
+                    geometry = vec![coord];
                 }
+                Some(_) => {}
             }
         }
         result
     }
 
-    fn read_ways(&mut self, file: std::fs::File) {
-        let mut pbf = osmpbfreader::OsmPbfReader::new(file);
-        for obj in pbf.iter().flatten() {
-            if let osmpbfreader::OsmObj::Way(way) = obj {
-                let mut skip = false;
-
-                match self.profile_type {
-                    ProfileType::Railway => {
-                        let mut properties = RailwayEdgeProperties::default();
-
-                        for (key, val) in way.tags.iter() {
-                            properties.update(key.to_string(), val.to_string());
-                            if self
-                                .forbidden
-                                .get(key.as_str())
-                                .map(|vals| vals.contains(val.as_str()) || vals.contains("*"))
-                                == Some(true)
-                            {
-                                skip = true;
-                            }
-                        }
-                        if properties.has_railway_tag && !skip {
-                            for node in &way.nodes {
-                                self.nodes_to_keep.insert(*node);
-                            }
-                            let railway_properties =
-                                EdgeProperties::RailwayEdgeProperties(properties);
-                            self.ways.push(Way {
-                                id: way.id,
-                                nodes: way.nodes,
-                                properties: railway_properties,
-                            });
+    // With a clip area, reads the coordinates of the nodes, so that the ways far from the area are
+    // skipped without being held in memory
+    fn read_node_coords(&mut self, file: &mut OsmFile) -> Result<(), Error> {
+        if self.clip.is_none() {
+            return Ok(());
+        }
+        let mut node_coords = Vec::new();
+        let mut lowest_node_id = self.lowest_node_id;
+        file.for_each(|obj| {
+            if let osmpbfreader::OsmObj::Node(node) = obj {
+                lowest_node_id = lowest_node_id.min(node.id.0);
+                node_coords.push((node.id, node.decimicro_lon, node.decimicro_lat));
+            }
+            Ok(())
+        })?;
+        // The files are usually sorted by id already
+        node_coords.sort_unstable_by_key(|&(id, _, _)| id);
+        self.node_coords = Some(node_coords);
+        self.lowest_node_id = lowest_node_id;
+        Ok(())
+    }
+
+    fn read_ways(&mut self, file: &mut OsmFile) -> Result<(), Error> {
+        let route_types = self.profile.route_types();
+        let node_coords = self.node_coords.take();
+        let area = self.clip.as_ref().map(|(area, _)| area.clone());
+        file.for_each(|obj| {
+            match obj {
+                osmpbfreader::OsmObj::Way(way) => {
+                    if let (Some(area), Some(node_coords)) = (&area, &node_coords) {
+                        if !near_area(area, node_coords, &way.nodes) {
+                            return Ok(());
                         }
                     }
-                    ProfileType::Road => {
-                        let mut properties = RoadEdgeProperties::default();
-                        for (key, val) in way.tags.iter() {
-                            properties.update(key.to_string(), val.to_string());
-                            if self
-                                .forbidden
-                                .get(key.as_str())
-                                .map(|vals| vals.contains(val.as_str()) || vals.contains("*"))
-                                == Some(true)
-                            {
-                                skip = true;
-                            }
-                        }
-                        properties.normalize();
-                        if properties.accessible() && !skip {
-                            for node in &way.nodes {
-                                self.nodes_to_keep.insert(*node);
-                            }
-                            let road_properties = EdgeProperties::RoadEdgeProperties(properties);
-                            self.ways.push(Way {
-                                id: way.id,
-                                nodes: way.nodes,
-                                properties: road_properties,
-                            });
+                    if let Some(lowest) = way.nodes.iter().map(|n| n.0).min() {
+                        self.lowest_node_id = self.lowest_node_id.min(lowest);
+                    }
+                    let mut skip = false;
+                    let mut properties = self.profile.clone();
+                    for (key, val) in way.tags.iter() {
+                        properties.update(key.to_string(), val.to_string());
+                        if self
+                            .forbidden
+                            .get(key.as_str())
+                            .map(|vals| vals.contains(val.as_str()) || vals.contains("*"))
+                            == Some(true)
+                        {
+                            skip = true;
                         }
                     }
-                };
+                    properties.normalize();
+                    if properties.keep() && !skip {
+                        self.nodes_to_keep.extend_from_slice(&way.nodes);
+                        self.ways.push(Way {
+                            id: way.id,
+                            nodes: way.nodes,
+                            properties,
+                        });
+                    }
+                }
+                osmpbfreader::OsmObj::Relation(relation) => {
+                    if let Some(restriction) = RestrictionRelation::from_relation(&relation) {
+                        self.restrictions.push(restriction);
+                    } else if let Some(route) =
+                        RouteRelation::from_relation(&relation, &route_types)
+                    {
+                        self.routes.push(route);
+                    }
+                }
+                _ => {}
             }
-        }
+            Ok(())
+        })
     }
 
-    fn read_nodes(&mut self, file: std::fs::File) {
-        let mut pbf = osmpbfreader::OsmPbfReader::new(file);
-        self.nodes.reserve(self.nodes_to_keep.len());
-        for obj in pbf.iter().flatten() {
+    fn read_nodes(&mut self, file: &mut OsmFile) -> Result<(), Error> {
+        self.nodes = NodeStore::new(std::mem::take(&mut self.nodes_to_keep));
+        file.for_each(|obj| {
             if let osmpbfreader::OsmObj::Node(node) = obj {
-                if self.nodes_to_keep.contains(&node.id) {
-                    self.nodes_to_keep.remove(&node.id);
-                    self.nodes.insert(
-                        node.id,
-                        Node {
-                            id: node.id,
-                            coord: Coord {
-                                lon: node.lon(),
-                                lat: node.lat(),
-                            },
-                            uses: 0,
-                        },
-                    );
+                if self.nodes.is_wanted(node.id) {
+                    let properties = self
+                        .profile
+                        .node_properties(&node.tags)
+                        .map(|properties| Arc::new(properties) as Arc<dyn NodeProps>);
+                    self.nodes
+                        .insert(node.id, node.decimicro_lon, node.decimicro_lat, properties);
                 }
             }
-        }
+            Ok(())
+        })
     }
 
-    fn nodes(self) -> Vec<Node> {
-        self.nodes
-            .into_values()
-            .filter(|node| node.uses > 1)
-            .collect()
+    fn load(&mut self, filename: &str) -> Result<(), Error> {
+        let path = std::path::Path::new(filename);
+        let mut file = OsmFile::open(path)?;
+        self.read_node_coords(&mut file)?;
+        self.read_ways(&mut file)?;
+        self.read_nodes(&mut file)?;
+        self.handle_missing_nodes()?;
+        self.clip_ways();
+        self.count_nodes_uses();
+        Ok(())
     }
 
-    fn edges(&self) -> Vec<Edge> {
-        self.ways
+    // Contracts the whole graph, keeping the edges used by the restrictions
+    // and the nodes where the routes change
+    fn contracted(&self, routes: &Routes) -> (Vec<Node>, Vec<Edge<P>>, Vec<Restriction>) {
+        let edges: Vec<Edge<P>> = self
+            .ways
             .iter()
             .flat_map(|way| self.split_way(way))
-            .collect()
+            .collect();
+        let restrictions = restrictions::resolve(&self.restrictions, &edges);
+
+        // The edges used by the restrictions must keep their ids
+        let restricted_edges: HashSet<&String> = restrictions
+            .iter()
+            .flat_map(|restriction| {
+                [&restriction.from_edge, &restriction.to_edge]
+                    .into_iter()
+                    .chain(&restriction.via_edges)
+            })
+            .collect();
+        let mut protected: HashSet<NodeId> = edges
+            .iter()
+            .filter(|edge| restricted_edges.contains(&edge.id))
+            .flat_map(|edge| [edge.source, edge.target])
+            .collect();
+
+        // A merged edge must belong to its routes over its whole length
+        let mut node_routes: HashMap<NodeId, &[usize]> = HashMap::new();
+        for edge in &edges {
+            let way_routes = routes.of_way(edge.osm_id);
+            for node_id in [edge.source, edge.target] {
+                if *node_routes.entry(node_id).or_insert(way_routes) != way_routes {
+                    protected.insert(node_id);
+                }
+            }
+        }
+        let (nodes, edges) = contraction::contract(
+            self.nodes.vertices().collect(),
+            edges,
+            &protected,
+            self.edge_ids,
+        );
+        (nodes, edges, restrictions)
     }
 
-    pub fn read(mut self, filename: &str) -> Result<(Vec<Node>, Vec<Edge>), String> {
-        let path = std::path::Path::new(filename);
-        let file = std::fs::File::open(path).map_err(|e| e.to_string())?;
-        self.read_ways(file);
-        let file_nodes = std::fs::File::open(path).map_err(|e| e.to_string())?;
-        self.read_nodes(file_nodes);
-        self.count_nodes_uses();
-        let edges = self.edges();
-        Ok((self.nodes(), edges))
+    pub fn read(self, filename: &str) -> Result<(Vec<Node>, Vec<Edge<P>>), Error> {
+        let (nodes, edges, _) = self.read_with_relations(filename)?;
+        Ok((nodes, edges))
+    }
+
+    // Same as read, but also returns the turn restrictions resolved onto the edges
+    #[allow(clippy::type_complexity)]
+    pub fn read_with_restrictions(
+        self,
+        filename: &str,
+    ) -> Result<(Vec<Node>, Vec<Edge<P>>, Vec<Restriction>), Error> {
+        let (nodes, edges, relations) = self.read_with_relations(filename)?;
+        Ok((nodes, edges, relations.restrictions))
+    }
+
+    // Same as read, but also returns the restrictions and routes resolved onto the edges
+    #[allow(clippy::type_complexity)]
+    pub fn read_with_relations(
+        self,
+        filename: &str,
+    ) -> Result<(Vec<Node>, Vec<Edge<P>>, EdgeRelations), Error> {
+        let mut sink = (Vec::new(), Vec::new());
+        let relations = self.read_into(filename, &mut sink)?;
+        let (nodes, edges) = sink;
+        Ok((nodes, edges, relations))
+    }
+
+    // Pushes the nodes, then the edges way by way, to the sink instead of returning them,
+    // so that the graph doesn't have to be held in memory. Returns the relations of the edges.
+    //
+    // The contraction needs the whole graph: with contract, the nodes and edges are only pushed
+    // once they are all contracted.
+    // A (NodeSink, EdgeSink) pair can be used to send the nodes and edges to different places.
+    pub fn read_into<S>(mut self, filename: &str, sink: &mut S) -> Result<EdgeRelations, Error>
+    where
+        S: NodeSink + EdgeSink<P>,
+    {
+        self.load(filename)?;
+        let routes = Routes::new(std::mem::take(&mut self.routes));
+        let mut relations = EdgeRelations::default();
+
+        if self.contract {
+            let (nodes, edges, restrictions) = self.contracted(&routes);
+            for node in nodes {
+                sink.push_node(node)?;
+            }
+            sink.finish_nodes()?;
+            for edge in edges {
+                relations.routes.extend(routes.members(&edge));
+                sink.push_edge(edge)?;
+            }
+            relations.restrictions = restrictions;
+        } else {
+            for node in self.nodes.vertices() {
+                sink.push_node(node)?;
+            }
+            sink.finish_nodes()?;
+
+            // Only the extremities of the edges of the ways used by the restrictions are kept to
+            // resolve them, without their geometry
+            let restricted_ways: HashSet<WayId> =
+                self.restrictions.iter().flat_map(|r| r.ways()).collect();
+            let mut restricted_edges = Vec::new();
+            for way in std::mem::take(&mut self.ways) {
+                for edge in self.split_way(&way) {
+                    if restricted_ways.contains(&edge.osm_id) {
+                        restricted_edges.push(Edge {
+                            id: edge.id.clone(),
+                            osm_id: edge.osm_id,
+                            osm_ids: Vec::new(),
+                            source: edge.source,
+                            target: edge.target,
+                            geometry: Vec::new(),
+                            properties: (),
+                        });
+                    }
+                    relations.routes.extend(routes.members(&edge));
+                    sink.push_edge(edge)?;
+                }
+            }
+            relations.restrictions = restrictions::resolve(&self.restrictions, &restricted_edges);
+        }
+        sink.finish_edges()?;
+        Ok(relations)
     }
 }
 
 // Read all the nodes and ways of the osm.pbf file
-pub fn read(filename: &str, profile_type: ProfileType) -> Result<(Vec<Node>, Vec<Edge>), String> {
-    Reader::new(profile_type).read(filename)
+pub fn read<P: Profile>(filename: &str, profile: P) -> Result<(Vec<Node>, Vec<Edge<P>>), Error> {
+    Reader::new(profile).read(filename)
 }
@@ -0,0 +1,217 @@
+use super::conditional::{parse_conditional, Condition};
+use super::models::{edge::Edge, restriction::Restriction};
+use osmpbfreader::objects::{NodeId, OsmId, Relation, RelationId, WayId};
+use std::collections::{HashMap, HashSet};
+
+// Value of one restriction tag of the relation: restriction, restriction:<mode>,
+// restriction:conditional or restriction:<mode>:conditional
+struct RestrictionTag {
+    restriction: String,
+    mode: String,
+    conditions: Vec<Condition>,
+}
+
+impl RestrictionTag {
+    // Returns the restrictions given by the tag, none if it isn't a restriction tag
+    fn parse(key: &str, value: &str) -> Vec<RestrictionTag> {
+        let suffix = match key.strip_prefix("restriction") {
+            Some("") => "",
+            Some(suffix) => match suffix.strip_prefix(':') {
+                Some(suffix) => suffix,
+                None => return Vec::new(),
+            },
+            None => return Vec::new(),
+        };
+        let conditional_mode = if suffix == "conditional" {
+            Some("")
+        } else {
+            suffix.strip_suffix(":conditional")
+        };
+        match conditional_mode {
+            Some(mode) => parse_conditional(key, value)
+                .into_iter()
+                .map(|conditional| RestrictionTag {
+                    restriction: conditional.value,
+                    mode: mode.to_string(),
+                    conditions: conditional.conditions,
+                })
+                .collect(),
+            None => vec![RestrictionTag {
+                restriction: value.to_string(),
+                mode: suffix.to_string(),
+                conditions: Vec::new(),
+            }],
+        }
+    }
+}
+
+// Turn restriction as represented in OpenStreetMap, referring to whole ways
+pub struct RestrictionRelation {
+    id: RelationId,
+    restrictions: Vec<RestrictionTag>,
+    except: String,
+    from: Vec<WayId>,
+    via_nodes: Vec<NodeId>,
+    via_ways: Vec<WayId>,
+    to: Vec<WayId>,
+}
+
+impl RestrictionRelation {
+    // Returns None if the relation is not a turn restriction
+    pub fn from_relation(relation: &Relation) -> Option<RestrictionRelation> {
+        if relation.tags.get("type").map(|t| t.as_str()) != Some("restriction") {
+            return None;
+        }
+        // The restriction can be limited to a transport mode (e.g. restriction:hgv) or to some
+        // conditions (e.g. restriction:conditional=no_u_turn @ (22:00-06:00)), and a relation
+        // can have several of these tags
+        let restrictions: Vec<RestrictionTag> = relation
+            .tags
+            .iter()
+            .flat_map(|(key, val)| RestrictionTag::parse(key, val))
+            .collect();
+        if restrictions.is_empty() {
+            return None;
+        }
+
+        let mut result = RestrictionRelation {
+            id: relation.id,
+            restrictions,
+            except: relation
+                .tags
+                .get("except")
+                .map(|val| val.to_string())
+                .unwrap_or_default(),
+            from: Vec::new(),
+            via_nodes: Vec::new(),
+            via_ways: Vec::new(),
+            to: Vec::new(),
+        };
+        for member in &relation.refs {
+            match (member.role.as_str(), member.member) {
+                ("from", OsmId::Way(way_id)) => result.from.push(way_id),
+                ("via", OsmId::Node(node_id)) => result.via_nodes.push(node_id),
+                ("via", OsmId::Way(way_id)) => result.via_ways.push(way_id),
+                ("to", OsmId::Way(way_id)) => result.to.push(way_id),
+                _ => {}
+            }
+        }
+        Some(result)
+    }
+
+    // Ways the restriction refers to
+    pub fn ways(&self) -> impl Iterator<Item = WayId> + '_ {
+        self.from
+            .iter()
+            .chain(&self.via_ways)
+            .chain(&self.to)
+            .copied()
+    }
+}
+
+// Finds the only edge of the way that touches one of the nodes
+fn touching_edge<'a, P>(
+    edges_by_way: &HashMap<WayId, Vec<&'a Edge<P>>>,
+    way_id: WayId,
+    nodes: &HashSet<NodeId>,
+) -> Option<&'a Edge<P>> {
+    let mut touching = edges_by_way
+        .get(&way_id)?
+        .iter()
+        .filter(|edge| nodes.contains(&edge.source) || nodes.contains(&edge.target));
+    match (touching.next(), touching.next()) {
+        (Some(edge), None) => Some(edge),
+        _ => None,
+    }
+}
+
+// Resolves the restrictions onto the split edges, one for each restriction tag of the relations
+// Restrictions that are not valid or refer to ways that are not in the graph are ignored
+pub fn resolve<P>(relations: &[RestrictionRelation], edges: &[Edge<P>]) -> Vec<Restriction> {
+    let mut edges_by_way: HashMap<WayId, Vec<&Edge<P>>> = HashMap::new();
+    for edge in edges {
+        edges_by_way.entry(edge.osm_id).or_default().push(edge);
+    }
+
+    let mut result = Vec::new();
+    for relation in relations {
+        if relation.from.len() != 1 || relation.to.len() != 1 {
+            continue;
+        }
+
+        let (via_node, via_edges, via) = match (&relation.via_nodes[..], &relation.via_ways[..]) {
+            ([node_id], []) => (Some(*node_id), Vec::new(), HashSet::from([*node_id])),
+            ([], via_ways) if !via_ways.is_empty() => {
+                let mut via_edges: Vec<&Edge<P>> = Vec::new();
+                for way_id in via_ways {
+                    via_edges.extend(edges_by_way.get(way_id).into_iter().flatten());
+                }
+                if via_edges.is_empty() {
+                    continue;
+                }
+                let via = via_edges
+                    .iter()
+                    .flat_map(|edge| [edge.source, edge.target])
+                    .collect();
+                (
+                    None,
+                    via_edges.iter().map(|edge| edge.id.clone()).collect(),
+                    via,
+                )
+            }
+            _ => continue,
+        };
+
+        let from_edge = touching_edge(&edges_by_way, relation.from[0], &via);
+        let to_edge = touching_edge(&edges_by_way, relation.to[0], &via);
+        if let (Some(from_edge), Some(to_edge)) = (from_edge, to_edge) {
+            result.extend(relation.restrictions.iter().map(|tag| Restriction {
+                id: relation.id,
+                restriction: tag.restriction.clone(),
+                mode: tag.mode.clone(),
+                conditions: tag.conditions.clone(),
+                except: relation.except.clone(),
+                from_edge: from_edge.id.clone(),
+                via_node,
+                via_edges: via_edges.clone(),
+                to_edge: to_edge.id.clone(),
+            }));
+        }
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn restriction_tags() {
+        let tags = RestrictionTag::parse("restriction", "no_left_turn");
+        assert_eq!(tags.len(), 1);
+        assert_eq!(tags[0].restriction, "no_left_turn");
+        assert!(tags[0].mode.is_empty() && tags[0].conditions.is_empty());
+
+        let tags = RestrictionTag::parse("restriction:hgv", "no_u_turn");
+        assert_eq!(tags[0].mode, "hgv");
+
+        let tags = RestrictionTag::parse(
+            "restriction:conditional",
+            "no_right_turn @ (Mo-Fr 07:00-09:00); only_straight_on @ wet",
+        );
+        assert_eq!(tags.len(), 2);
+        assert!(tags[0].mode.is_empty());
+        assert_eq!(tags[1].restriction, "only_straight_on");
+        assert_eq!(
+            tags[1].conditions,
+            vec![Condition::Other("wet".to_string())]
+        );
+
+        let tags = RestrictionTag::parse("restriction:bus:conditional", "no_entry @ (22:00-06:00)");
+        assert_eq!(tags[0].mode, "bus");
+        assert_eq!(tags[0].conditions.len(), 1);
+
+        assert!(RestrictionTag::parse("restrictions", "no_left_turn").is_empty());
+        assert!(RestrictionTag::parse("type", "restriction").is_empty());
+    }
+}
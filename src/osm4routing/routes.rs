@@ -0,0 +1,110 @@
+use super::models::{edge::Edge, route::RouteMember};
+use osmpbfreader::objects::{OsmId, Relation, RelationId, WayId};
+use std::collections::HashMap;
+
+// Route as represented in OpenStreetMap, referring to whole ways
+pub struct RouteRelation {
+    id: RelationId,
+    route: String,
+    reference: String,
+    name: String,
+    operator: String,
+    ways: Vec<WayId>,
+}
+
+impl RouteRelation {
+    // Returns None if the relation is not a route of one of the types
+    pub fn from_relation(relation: &Relation, route_types: &[String]) -> Option<RouteRelation> {
+        if relation.tags.get("type").map(|t| t.as_str()) != Some("route") {
+            return None;
+        }
+        let route = relation.tags.get("route")?;
+        if !route_types
+            .iter()
+            .any(|route_type| route_type == route.as_str())
+        {
+            return None;
+        }
+        let tag = |key: &str| {
+            relation
+                .tags
+                .get(key)
+                .map(|val| val.to_string())
+                .unwrap_or_default()
+        };
+        Some(RouteRelation {
+            id: relation.id,
+            route: route.to_string(),
+            reference: tag("ref"),
+            name: tag("name"),
+            operator: tag("operator"),
+            // The platforms and stops are not part of the path of the route
+            ways: relation
+                .refs
+                .iter()
+                .filter(|member| {
+                    !member.role.starts_with("platform") && !member.role.starts_with("stop")
+                })
+                .filter_map(|member| match member.member {
+                    OsmId::Way(way_id) => Some(way_id),
+                    _ => None,
+                })
+                .collect(),
+        })
+    }
+}
+
+// Routes resolves the route relations onto the edges, one edge at a time
+pub struct Routes {
+    relations: Vec<RouteRelation>,
+    by_way: HashMap<WayId, Vec<usize>>,
+}
+
+impl Routes {
+    pub fn new(relations: Vec<RouteRelation>) -> Routes {
+        let mut by_way: HashMap<WayId, Vec<usize>> = HashMap::new();
+        for (i, relation) in relations.iter().enumerate() {
+            for way_id in &relation.ways {
+                let routes = by_way.entry(*way_id).or_default();
+                // A way can be listed twice, e.g. once for each direction
+                if routes.last() != Some(&i) {
+                    routes.push(i);
+                }
+            }
+        }
+        Routes { relations, by_way }
+    }
+
+    // Indexes of the routes of the way, in increasing order
+    pub fn of_way(&self, way_id: WayId) -> &[usize] {
+        self.by_way
+            .get(&way_id)
+            .map(|routes| routes.as_slice())
+            .unwrap_or_default()
+    }
+
+    // Routes of the edge, those of every way it comes from when it was contracted
+    pub fn members<P>(&self, edge: &Edge<P>) -> Vec<RouteMember> {
+        let mut relations: Vec<usize> = edge
+            .osm_ids
+            .iter()
+            .flat_map(|way_id| self.by_way.get(way_id).into_iter().flatten().copied())
+            .collect();
+        relations.sort_unstable();
+        relations.dedup();
+        relations
+            .into_iter()
+            .map(|i| {
+                let relation = &self.relations[i];
+                RouteMember {
+                    edge_id: edge.id.clone(),
+                    relation_id: relation.id,
+                    route: relation.route.clone(),
+                    reference: relation.reference.clone(),
+                    name: relation.name.clone(),
+                    operator: relation.operator.clone(),
+                }
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,59 @@
+use super::error::Error;
+use super::models::{edge::Edge, node::Node};
+
+// NodeSink receives the vertices of the graph as soon as the reader knows them
+// See Reader::read_into
+pub trait NodeSink {
+    fn push_node(&mut self, node: Node) -> Result<(), Error>;
+
+    // Called once every node was pushed
+    fn finish_nodes(&mut self) -> Result<(), Error> {
+        Ok(())
+    }
+}
+
+// EdgeSink receives the edges of the graph as soon as the reader splits them out of the ways
+pub trait EdgeSink<P> {
+    fn push_edge(&mut self, edge: Edge<P>) -> Result<(), Error>;
+
+    // Called once every edge was pushed
+    fn finish_edges(&mut self) -> Result<(), Error> {
+        Ok(())
+    }
+}
+
+// Collecting the nodes in memory, as done by Reader::read
+impl NodeSink for Vec<Node> {
+    fn push_node(&mut self, node: Node) -> Result<(), Error> {
+        self.push(node);
+        Ok(())
+    }
+}
+
+impl<P> EdgeSink<P> for Vec<Edge<P>> {
+    fn push_edge(&mut self, edge: Edge<P>) -> Result<(), Error> {
+        self.push(edge);
+        Ok(())
+    }
+}
+
+// A pair of sinks, e.g. to write the nodes and the edges to different places
+impl<N: NodeSink, E> NodeSink for (N, E) {
+    fn push_node(&mut self, node: Node) -> Result<(), Error> {
+        self.0.push_node(node)
+    }
+
+    fn finish_nodes(&mut self) -> Result<(), Error> {
+        self.0.finish_nodes()
+    }
+}
+
+impl<P, N, E: EdgeSink<P>> EdgeSink<P> for (N, E) {
+    fn push_edge(&mut self, edge: Edge<P>) -> Result<(), Error> {
+        self.1.push_edge(edge)
+    }
+
+    fn finish_edges(&mut self) -> Result<(), Error> {
+        self.1.finish_edges()
+    }
+}
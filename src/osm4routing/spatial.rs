@@ -0,0 +1,92 @@
+use super::models::{coord::Coord, edge::Edge};
+use rstar::primitives::{GeomWithData, Line};
+use rstar::RTree;
+
+// Position on an edge closest to a coordinate
+#[derive(Clone, Debug)]
+pub struct Snap {
+    // Index of the edge in Graph::edges
+    pub edge: usize,
+    pub edge_id: String,
+    // Projection of the coordinate on the edge
+    pub point: Coord,
+    // Position of the point along the edge, from 0 at the source to 1 at the target
+    pub fraction: f64,
+    // Distance in meters between the coordinate and the point
+    pub distance: f64,
+}
+
+// Segment of the geometry of an edge: index of the edge and of the first coordinate of the segment
+type Segment = GeomWithData<Line<[f64; 2]>, (usize, usize)>;
+
+// EdgeIndex is an R-tree of the segments of the edge geometries
+//
+// The coordinates are indexed with the longitude scaled by the cosine of the mean latitude,
+// so that distances are almost isotropic for extracts of a city or a country.
+pub struct EdgeIndex {
+    tree: RTree<Segment>,
+    scale: f64,
+}
+
+impl EdgeIndex {
+    pub fn new<P>(edges: &[Edge<P>]) -> EdgeIndex {
+        let (sum, count) = edges
+            .iter()
+            .flat_map(|edge| &edge.geometry)
+            .fold((0., 0), |(sum, count), coord| (sum + coord.lat, count + 1));
+        let mean_lat: f64 = if count > 0 { sum / count as f64 } else { 0. };
+        let mut index = EdgeIndex {
+            tree: RTree::new(),
+            scale: mean_lat.to_radians().cos(),
+        };
+        let segments = edges
+            .iter()
+            .enumerate()
+            .flat_map(|(i, edge)| {
+                edge.geometry
+                    .windows(2)
+                    .enumerate()
+                    .map(move |(j, pair)| (i, j, pair))
+            })
+            .map(|(i, j, pair)| {
+                let line = Line::new(index.point(&pair[0]), index.point(&pair[1]));
+                GeomWithData::new(line, (i, j))
+            })
+            .collect();
+        index.tree = RTree::bulk_load(segments);
+        index
+    }
+
+    fn point(&self, coord: &Coord) -> [f64; 2] {
+        [coord.lon * self.scale, coord.lat]
+    }
+
+    // Segments by increasing distance to the coordinate: index of the edge and of the segment
+    pub fn nearest_segments(&self, coord: &Coord) -> impl Iterator<Item = (usize, usize)> + '_ {
+        self.tree
+            .nearest_neighbor_iter(&self.point(coord))
+            .map(|segment| segment.data)
+    }
+
+    // Projects the coordinate on the segment of the edge
+    // Returns the projected point and its distance in meters from the start of the edge
+    pub fn project<P>(&self, edge: &Edge<P>, segment: usize, coord: &Coord) -> (Coord, f64) {
+        let (a, b) = (edge.geometry[segment], edge.geometry[segment + 1]);
+        let ([ax, ay], [bx, by], [x, y]) = (self.point(&a), self.point(&b), self.point(coord));
+        let squared_length = (bx - ax).powi(2) + (by - ay).powi(2);
+        let t = if squared_length > 0. {
+            (((x - ax) * (bx - ax) + (y - ay) * (by - ay)) / squared_length).clamp(0., 1.)
+        } else {
+            0.
+        };
+        let point = Coord {
+            lon: a.lon + t * (b.lon - a.lon),
+            lat: a.lat + t * (b.lat - a.lat),
+        };
+        let before: f64 = edge.geometry[..=segment]
+            .windows(2)
+            .map(|pair| pair[0].distance(&pair[1]))
+            .sum();
+        (point, before + a.distance(&point))
+    }
+}
@@ -1,56 +1,90 @@
-use super::super::categorize::edge_properties::EdgeProperties;
+use super::super::components::Components;
+use super::super::error::Error;
 use super::super::models::edge::Edge;
-use crate::ProfileType;
-
-pub fn create_edges_csv(edges: Vec<Edge>, profile_type: ProfileType) {
-    let edges_path = std::path::Path::new("edges.csv");
-    let mut edges_csv = csv::Writer::from_path(edges_path).unwrap();
-
-    let edges_csv_columns = match profile_type {
-        ProfileType::Railway => vec!["edge_id", "railway_type", "usage", "service", "geometry"],
-        ProfileType::Road => vec![
-            "edge_id",
-            "foot",
-            "car_forward",
-            "car_backward",
-            "bike_forward",
-            "bike_backward",
-            "train",
-            "wkt",
-        ],
-    };
-
-    edges_csv
-        .serialize(edges_csv_columns)
-        .expect("CSV: unable to write edge header");
-
-    for edge in &edges {
-        match &edge.properties {
-            EdgeProperties::RailwayEdgeProperties(edge_properties) => {
-                edges_csv
-                    .serialize((
-                        &edge.id,
-                        edge_properties.railway_type.clone(),
-                        edge_properties.usage.clone(),
-                        edge_properties.service.clone(),
-                        edge.as_wkt(),
-                    ))
-                    .expect("CSV: unable to write edge");
-            }
-            EdgeProperties::RoadEdgeProperties(edge_properties) => {
-                edges_csv
-                    .serialize((
-                        &edge.id,
-                        edge_properties.foot,
-                        edge_properties.car_forward,
-                        edge_properties.car_backward,
-                        edge_properties.bike_forward,
-                        edge_properties.bike_backward,
-                        edge_properties.train,
-                        edge.as_wkt(),
-                    ))
-                    .expect("CSV: unable to write edge");
-            }
+use super::super::profile::{Mode, Profile};
+use super::super::sink::EdgeSink;
+use std::fs::File;
+use std::path::Path;
+
+// EdgesCsv writes the edges to a csv file one by one
+pub struct EdgesCsv<'a> {
+    csv: csv::Writer<File>,
+    modes: Vec<Mode>,
+    components: Option<&'a Components>,
+}
+
+impl<'a> EdgesCsv<'a> {
+    // The components need the whole graph, they can't be given when the edges are streamed
+    pub fn new<P: Profile>(
+        profile: &P,
+        components: Option<&'a Components>,
+        edges_path: &Path,
+    ) -> Result<EdgesCsv<'a>, Error> {
+        let mut csv = csv::Writer::from_path(edges_path)?;
+        let modes = profile.modes();
+
+        let mut edges_csv_columns = ["edge_id", "source", "target", "osm_ids"]
+            .map(String::from)
+            .to_vec();
+        edges_csv_columns.extend(profile.columns());
+        edges_csv_columns.push("length".to_string());
+        edges_csv_columns.extend(modes.iter().map(|mode| format!("{}_time", mode.name())));
+        if let Some(components) = components {
+            edges_csv_columns.extend(components.columns.iter().cloned());
+        }
+        edges_csv_columns.push(profile.geometry_column().to_string());
+        csv.write_record(&edges_csv_columns)?;
+
+        Ok(EdgesCsv {
+            csv,
+            modes,
+            components,
+        })
+    }
+
+    pub fn write<P: Profile>(&mut self, edge: &Edge<P>) -> Result<(), Error> {
+        // Several ways when the edges were contracted, separated by ";"
+        let osm_ids: Vec<String> = edge.osm_ids.iter().map(|id| id.0.to_string()).collect();
+        let mut record = vec![
+            edge.id.clone(),
+            edge.source.0.to_string(),
+            edge.target.0.to_string(),
+            osm_ids.join(";"),
+        ];
+        record.extend(edge.properties.row().iter().map(|value| value.to_string()));
+        record.push(edge.length().to_string());
+        for mode in &self.modes {
+            record.push(
+                edge.travel_time(*mode)
+                    .map(|time| time.to_string())
+                    .unwrap_or_default(),
+            );
         }
+        if let Some(components) = self.components {
+            record.extend(
+                components
+                    .row(&edge.id)
+                    .iter()
+                    .map(|id| id.map(|id| id.to_string()).unwrap_or_default()),
+            );
+        }
+        record.push(edge.as_wkt());
+        self.csv.write_record(&record)?;
+        Ok(())
+    }
+
+    pub fn finish(&mut self) -> Result<(), Error> {
+        self.csv.flush()?;
+        Ok(())
+    }
+}
+
+impl<P: Profile> EdgeSink<P> for EdgesCsv<'_> {
+    fn push_edge(&mut self, edge: Edge<P>) -> Result<(), Error> {
+        self.write(&edge)
+    }
+
+    fn finish_edges(&mut self) -> Result<(), Error> {
+        self.finish()
     }
 }
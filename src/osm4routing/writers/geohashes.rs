@@ -1,95 +1,71 @@
+use super::super::error::Error;
 use geohashrust::BinaryHash;
-use polars::prelude::*;
+use std::collections::HashMap;
+use std::path::Path;
 
-pub fn create_geohashes_csv(geohashes: Vec<String>, geohash_precision: u8) {
-    let max_allowed_features_in_geohash = 1000;
+const MAX_ALLOWED_FEATURES_IN_GEOHASH: usize = 1000;
 
-    let mut final_geohashes: Vec<String> = Vec::new();
-    let mut temp_geohashes: Vec<String> = geohashes;
-
-    for i in 0 + 1..geohash_precision as usize {
-        let sliced_geohashes: Vec<&str> =
-            temp_geohashes.iter().map(|geohash| &geohash[..i]).collect();
-        let geohash_df =
-            df!("geohash" => temp_geohashes.clone(), "sliced_geohash" => sliced_geohashes).unwrap();
-
-        let grouped_geohash_df = geohash_df
-            .lazy()
-            .groupby([col("sliced_geohash")])
-            .agg([
-                col("geohash").count().alias("count"),
-                col("geohash").reverse().alias("geohashes"),
-            ])
-            .collect()
-            .unwrap();
-
-        let geohashes_over_max_allowed_features_df = grouped_geohash_df
-            .clone()
-            .lazy()
-            .filter(col("count").gt(lit(max_allowed_features_in_geohash)))
-            .collect()
-            .unwrap();
-
-        let geohashes_over_max_allowed_features_vec: Vec<String> =
-            geohashes_over_max_allowed_features_df
-                .column("geohashes")
-                .unwrap()
-                .explode()
-                .unwrap()
-                .utf8()
-                .unwrap()
-                .into_no_null_iter()
-                .collect::<Vec<&str>>()
-                .iter()
-                .map(|geohash| geohash.to_string())
-                .collect();
+// Number of edges in each geohash cell, the cells being the binary geohashes of the full precision
+// as integers. The cells are counted while the edges are written, so the memory only grows with the
+// number of distinct cells.
+pub type GeohashCounts = HashMap<u64, usize>;
 
-        temp_geohashes = geohashes_over_max_allowed_features_vec;
-
-        let geohashes_under_max_allowed_features_df = grouped_geohash_df
-            .clone()
-            .lazy()
-            .filter(col("count").lt(lit(max_allowed_features_in_geohash + 1)))
-            .collect()
-            .unwrap();
+// Counts an edge in each of its geohashes, see Edge::get_geohashes
+pub fn count_geohashes(counts: &mut GeohashCounts, geohashes: &[String]) {
+    for geohash in geohashes {
+        if let Ok(cell) = u64::from_str_radix(geohash, 2) {
+            *counts.entry(cell).or_default() += 1;
+        }
+    }
+}
 
-        let mut geohashes_under_max_allowed_features_vec: Vec<String> =
-            geohashes_under_max_allowed_features_df
-                .column("sliced_geohash")
-                .unwrap()
-                .utf8()
-                .unwrap()
-                .into_no_null_iter()
-                .collect::<Vec<&str>>()
-                .iter()
-                .map(|geohash| geohash.to_string())
-                .collect();
+// Writes the largest geohashes with at most MAX_ALLOWED_FEATURES_IN_GEOHASH edges, the cells of the
+// full precision being written when they have more
+pub fn create_geohashes_csv(
+    counts: &GeohashCounts,
+    geohash_precision: u8,
+    geohashes_path: &Path,
+) -> Result<(), Error> {
+    let precision = geohash_precision as usize;
+    let mut final_geohashes: Vec<String> = Vec::new();
+    let mut remaining: Vec<(u64, usize)> =
+        counts.iter().map(|(&cell, &count)| (cell, count)).collect();
+    remaining.sort_unstable();
 
-        final_geohashes.append(&mut geohashes_under_max_allowed_features_vec);
+    for i in 1..precision {
+        let shift = precision - i;
+        let mut over_max_allowed_features = Vec::new();
+        // The cells are sorted, so the cells of a geohash are contiguous
+        for cells in remaining.chunk_by(|a, b| a.0 >> shift == b.0 >> shift) {
+            let count: usize = cells.iter().map(|&(_, count)| count).sum();
+            if count > MAX_ALLOWED_FEATURES_IN_GEOHASH {
+                over_max_allowed_features.extend_from_slice(cells);
+            } else {
+                final_geohashes.push(format!("{:0width$b}", cells[0].0 >> shift, width = i));
+            }
+        }
+        remaining = over_max_allowed_features;
     }
+    final_geohashes.extend(
+        remaining
+            .iter()
+            .map(|&(cell, _)| format!("{:0width$b}", cell, width = precision)),
+    );
 
-    // remove duplicated geohashes
-    temp_geohashes.sort_unstable();
-    temp_geohashes.dedup();
-    final_geohashes.append(&mut temp_geohashes);
-
-    let geohashes_path = std::path::Path::new("geohashes.csv");
-    let mut geohashes_csv = csv::Writer::from_path(geohashes_path).unwrap();
-    geohashes_csv
-        .serialize(vec!["geohash", "min_lon", "min_lat", "max_lon", "max_lat"])
-        .expect("CSV: unable to write geohash header");
+    let mut geohashes_csv = csv::Writer::from_path(geohashes_path)?;
+    geohashes_csv.serialize(vec!["geohash", "min_lon", "min_lat", "max_lon", "max_lat"])?;
 
     for final_geohash in final_geohashes {
         let bh = BinaryHash::from_string(final_geohash.as_str());
         let bbox = bh.decode();
-        geohashes_csv
-            .serialize((
-                final_geohash,
-                bbox.min_lon,
-                bbox.min_lat,
-                bbox.max_lon,
-                bbox.max_lat,
-            ))
-            .expect("CSV: unable to write geohash");
+        geohashes_csv.serialize((
+            final_geohash,
+            bbox.min_lon,
+            bbox.min_lat,
+            bbox.max_lon,
+            bbox.max_lat,
+        ))?;
     }
+    geohashes_csv.flush()?;
+    Ok(())
 }
@@ -0,0 +1,125 @@
+use super::super::components::Components;
+use super::super::error::Error;
+use super::super::models::{edge::Edge, node::Node};
+use super::super::profile::{Profile, PropertyValue};
+use serde_json::{json, Map, Value};
+use std::io::Write;
+use std::path::Path;
+
+// Features are streamed one by one to avoid building the whole collection in memory
+fn write_feature_collection<I>(path: &Path, features: I) -> Result<(), Error>
+where
+    I: Iterator<Item = Value>,
+{
+    let mut file = std::io::BufWriter::new(std::fs::File::create(path)?);
+    write!(file, r#"{{"type":"FeatureCollection","features":["#)?;
+    for (i, feature) in features.enumerate() {
+        if i > 0 {
+            writeln!(file, ",")?;
+        } else {
+            writeln!(file)?;
+        }
+        serde_json::to_writer(&mut file, &feature)?;
+    }
+    writeln!(file, "\n]}}")?;
+    file.flush()?;
+    Ok(())
+}
+
+fn json_value(value: PropertyValue) -> Value {
+    match value {
+        PropertyValue::Category(value) | PropertyValue::Text(value) => json!(value),
+        PropertyValue::Number(value) => json!(value),
+        PropertyValue::Boolean(value) => json!(value),
+    }
+}
+
+fn edge_properties<P: Profile>(
+    edge: &Edge<P>,
+    components: Option<&Components>,
+) -> Map<String, Value> {
+    let mut properties = Map::new();
+    properties.insert("id".to_string(), json!(edge.id));
+    properties.insert("source".to_string(), json!(edge.source.0));
+    properties.insert("target".to_string(), json!(edge.target.0));
+    properties.insert("osm_id".to_string(), json!(edge.osm_id.0));
+    let osm_ids: Vec<i64> = edge.osm_ids.iter().map(|id| id.0).collect();
+    properties.insert("osm_ids".to_string(), json!(osm_ids));
+    properties.insert("length".to_string(), json!(edge.length()));
+
+    for (column, value) in edge
+        .properties
+        .columns()
+        .into_iter()
+        .zip(edge.properties.row())
+    {
+        properties.insert(column, json_value(value));
+    }
+    for mode in edge.properties.modes() {
+        properties.insert(
+            format!("{}_time", mode.name()),
+            json!(edge.travel_time(mode)),
+        );
+    }
+    if let Some(components) = components {
+        for (column, id) in components.columns.iter().zip(components.row(&edge.id)) {
+            properties.insert(column.clone(), json!(id));
+        }
+    }
+    properties
+}
+
+fn node_properties<P: Profile>(node: &Node, profile: &P) -> Map<String, Value> {
+    let mut properties = Map::new();
+    properties.insert("id".to_string(), json!(node.id.0));
+
+    for (column, value) in profile
+        .node_columns()
+        .into_iter()
+        .zip(profile.node_row(node))
+    {
+        properties.insert(column, json_value(value));
+    }
+    properties
+}
+
+pub fn create_nodes_geojson<P: Profile>(
+    nodes: &[Node],
+    profile: &P,
+    nodes_path: &Path,
+) -> Result<(), Error> {
+    let features = nodes.iter().map(|node| {
+        json!({
+            "type": "Feature",
+            "geometry": {
+                "type": "Point",
+                "coordinates": [node.coord.lon, node.coord.lat],
+            },
+            "properties": node_properties(node, profile),
+        })
+    });
+    write_feature_collection(nodes_path, features)
+}
+
+pub fn create_edges_geojson<P: Profile>(
+    edges: &[Edge<P>],
+    components: Option<&Components>,
+    edges_path: &Path,
+) -> Result<(), Error> {
+    let features = edges.iter().map(|edge| {
+        let coordinates: Vec<[f64; 2]> = edge
+            .geometry
+            .iter()
+            .map(|coord| [coord.lon, coord.lat])
+            .collect();
+        json!({
+            "type": "Feature",
+            "geometry": {
+                "type": "LineString",
+                "coordinates": coordinates,
+            },
+            "properties": edge_properties(edge, components),
+        })
+    });
+    write_feature_collection(edges_path, features)
+}
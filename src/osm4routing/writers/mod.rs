@@ -1,4 +1,8 @@
 pub mod edges;
 pub mod geohashes;
+pub mod geojson;
 pub mod nodes;
+pub mod parquet;
+pub mod restrictions;
+pub mod routes;
 pub mod writer;
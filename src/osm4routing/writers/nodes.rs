@@ -1,14 +1,56 @@
+use super::super::error::Error;
 use super::super::models::node::Node;
+use super::super::profile::Profile;
+use super::super::sink::NodeSink;
+use std::fs::File;
+use std::path::Path;
 
-pub fn create_nodes_csv(nodes: Vec<Node>) {
-    let nodes_path = std::path::Path::new("nodes.csv");
-    let mut nodes_csv = csv::Writer::from_path(nodes_path).unwrap();
-    nodes_csv
-        .serialize(vec!["id", "lon", "lat"])
-        .expect("CSV: unable to write node header");
-    for node in nodes {
-        nodes_csv
-            .serialize((node.id.0, node.coord.lon, node.coord.lat))
-            .expect("CSV: unable to write node");
+// NodesCsv writes the nodes to a csv file one by one
+pub struct NodesCsv<P> {
+    csv: csv::Writer<File>,
+    profile: P,
+}
+
+impl<P: Profile> NodesCsv<P> {
+    pub fn new(profile: &P, nodes_path: &Path) -> Result<NodesCsv<P>, Error> {
+        let mut csv = csv::Writer::from_path(nodes_path)?;
+        let mut nodes_csv_columns = vec!["id".to_string(), "lon".to_string(), "lat".to_string()];
+        nodes_csv_columns.extend(profile.node_columns());
+        csv.write_record(&nodes_csv_columns)?;
+        Ok(NodesCsv {
+            csv,
+            profile: profile.clone(),
+        })
+    }
+
+    pub fn write(&mut self, node: &Node) -> Result<(), Error> {
+        let mut record = vec![
+            node.id.0.to_string(),
+            node.coord.lon.to_string(),
+            node.coord.lat.to_string(),
+        ];
+        record.extend(
+            self.profile
+                .node_row(node)
+                .iter()
+                .map(|value| value.to_string()),
+        );
+        self.csv.write_record(&record)?;
+        Ok(())
+    }
+
+    pub fn finish(&mut self) -> Result<(), Error> {
+        self.csv.flush()?;
+        Ok(())
+    }
+}
+
+impl<P: Profile> NodeSink for NodesCsv<P> {
+    fn push_node(&mut self, node: Node) -> Result<(), Error> {
+        self.write(&node)
+    }
+
+    fn finish_nodes(&mut self) -> Result<(), Error> {
+        self.finish()
     }
 }
@@ -0,0 +1,189 @@
+use super::super::components::Components;
+use super::super::error::Error;
+use super::super::models::{edge::Edge, node::Node};
+use super::super::profile::{Profile, PropertyValue};
+use polars::prelude::*;
+use serde_json::json;
+use std::path::Path;
+
+// Point in the well known binary format (little endian)
+fn point_wkb(lon: f64, lat: f64) -> Vec<u8> {
+    let mut wkb = Vec::with_capacity(21);
+    wkb.push(1);
+    wkb.extend_from_slice(&1u32.to_le_bytes());
+    wkb.extend_from_slice(&lon.to_le_bytes());
+    wkb.extend_from_slice(&lat.to_le_bytes());
+    wkb
+}
+
+fn categorical(name: &str, values: Vec<String>) -> Result<Column, Error> {
+    let dtype = DataType::from_categories(Categories::global());
+    Ok(Column::new(name.into(), values).cast(&dtype)?)
+}
+
+// Builds one column per property, with the type matching its values
+fn property_columns(
+    names: Vec<String>,
+    rows: Vec<Vec<PropertyValue>>,
+) -> Result<Vec<Column>, Error> {
+    let mut values: Vec<Vec<PropertyValue>> = vec![Vec::with_capacity(rows.len()); names.len()];
+    for row in rows {
+        for (column, value) in values.iter_mut().zip(row) {
+            column.push(value);
+        }
+    }
+
+    let mut columns = Vec::with_capacity(names.len());
+    for (name, column_values) in names.into_iter().zip(values) {
+        let column = match column_values.first() {
+            Some(PropertyValue::Number(_)) => {
+                let numbers: Vec<Option<f64>> = column_values
+                    .into_iter()
+                    .map(|value| match value {
+                        PropertyValue::Number(number) => number,
+                        _ => None,
+                    })
+                    .collect();
+                Column::new(name.into(), numbers)
+            }
+            Some(PropertyValue::Boolean(_)) => {
+                let booleans: Vec<Option<bool>> = column_values
+                    .into_iter()
+                    .map(|value| match value {
+                        PropertyValue::Boolean(boolean) => boolean,
+                        _ => None,
+                    })
+                    .collect();
+                Column::new(name.into(), booleans)
+            }
+            Some(PropertyValue::Category(_)) => categorical(
+                &name,
+                column_values
+                    .iter()
+                    .map(|value| value.to_string())
+                    .collect(),
+            )?,
+            _ => {
+                let texts: Vec<String> = column_values
+                    .iter()
+                    .map(|value| value.to_string())
+                    .collect();
+                Column::new(name.into(), texts)
+            }
+        };
+        columns.push(column);
+    }
+    Ok(columns)
+}
+
+pub fn nodes_dataframe<P: Profile>(nodes: &[Node], profile: &P) -> Result<DataFrame, Error> {
+    let ids: Vec<i64> = nodes.iter().map(|node| node.id.0).collect();
+    let lons: Vec<f64> = nodes.iter().map(|node| node.coord.lon).collect();
+    let lats: Vec<f64> = nodes.iter().map(|node| node.coord.lat).collect();
+    let geometries: Vec<Vec<u8>> = nodes
+        .iter()
+        .map(|node| point_wkb(node.coord.lon, node.coord.lat))
+        .collect();
+
+    let mut columns = vec![
+        Column::new("id".into(), ids),
+        Column::new("lon".into(), lons),
+        Column::new("lat".into(), lats),
+    ];
+    let rows = nodes.iter().map(|node| profile.node_row(node)).collect();
+    columns.extend(property_columns(profile.node_columns(), rows)?);
+
+    columns.push(Column::new("geometry".into(), geometries));
+    Ok(DataFrame::new(columns)?)
+}
+
+pub fn edges_dataframe<P: Profile>(
+    edges: &[Edge<P>],
+    profile: &P,
+    components: Option<&Components>,
+) -> Result<DataFrame, Error> {
+    let ids: Vec<String> = edges.iter().map(|edge| edge.id.clone()).collect();
+    let osm_ids: Vec<i64> = edges.iter().map(|edge| edge.osm_id.0).collect();
+    let osm_ids_lists: Vec<Series> = edges
+        .iter()
+        .map(|edge| {
+            let ids: Vec<i64> = edge.osm_ids.iter().map(|id| id.0).collect();
+            Series::new("".into(), ids)
+        })
+        .collect();
+    let sources: Vec<i64> = edges.iter().map(|edge| edge.source.0).collect();
+    let targets: Vec<i64> = edges.iter().map(|edge| edge.target.0).collect();
+    let lengths: Vec<f64> = edges.iter().map(|edge| edge.length()).collect();
+    let geometries: Vec<Vec<u8>> = edges.iter().map(|edge| edge.as_wkb()).collect();
+
+    let mut columns = vec![
+        Column::new("edge_id".into(), ids),
+        Column::new("source".into(), sources),
+        Column::new("target".into(), targets),
+        Column::new("osm_id".into(), osm_ids),
+        Column::new("osm_ids".into(), osm_ids_lists),
+        Column::new("length".into(), lengths),
+    ];
+    let rows = edges.iter().map(|edge| edge.properties.row()).collect();
+    columns.extend(property_columns(profile.columns(), rows)?);
+
+    for mode in profile.modes() {
+        let times: Vec<Option<f64>> = edges.iter().map(|edge| edge.travel_time(mode)).collect();
+        columns.push(Column::new(format!("{}_time", mode.name()).into(), times));
+    }
+
+    if let Some(components) = components {
+        let rows: Vec<Vec<Option<usize>>> =
+            edges.iter().map(|edge| components.row(&edge.id)).collect();
+        for (i, name) in components.columns.iter().enumerate() {
+            let ids: Vec<Option<u64>> = rows.iter().map(|row| row[i].map(|id| id as u64)).collect();
+            columns.push(Column::new(name.into(), ids));
+        }
+    }
+
+    columns.push(Column::new("geometry".into(), geometries));
+    Ok(DataFrame::new(columns)?)
+}
+
+// Writes the dataframe with the GeoParquet metadata describing its WKB "geometry" column
+// See https://geoparquet.org/releases/v1.0.0/
+fn write_geoparquet(df: &mut DataFrame, path: &Path, geometry_type: &str) -> Result<(), Error> {
+    let geo = json!({
+        "version": "1.0.0",
+        "primary_column": "geometry",
+        "columns": {
+            "geometry": {
+                "encoding": "WKB",
+                "geometry_types": [geometry_type],
+            },
+        },
+    });
+    let metadata = KeyValueMetadata::from_static(vec![("geo".to_string(), geo.to_string())]);
+
+    let file = std::fs::File::create(path)?;
+    ParquetWriter::new(file)
+        .with_key_value_metadata(Some(metadata))
+        .finish(df)?;
+    Ok(())
+}
+
+pub fn create_nodes_parquet<P: Profile>(
+    nodes: &[Node],
+    profile: &P,
+    nodes_path: &Path,
+) -> Result<(), Error> {
+    write_geoparquet(&mut nodes_dataframe(nodes, profile)?, nodes_path, "Point")
+}
+
+pub fn create_edges_parquet<P: Profile>(
+    edges: &[Edge<P>],
+    profile: &P,
+    components: Option<&Components>,
+    edges_path: &Path,
+) -> Result<(), Error> {
+    write_geoparquet(
+        &mut edges_dataframe(edges, profile, components)?,
+        edges_path,
+        "LineString",
+    )
+}
@@ -0,0 +1,41 @@
+use super::super::error::Error;
+use super::super::models::restriction::Restriction;
+use std::path::Path;
+
+pub fn create_restrictions_csv(
+    restrictions: &[Restriction],
+    restrictions_path: &Path,
+) -> Result<(), Error> {
+    let mut restrictions_csv = csv::Writer::from_path(restrictions_path)?;
+    restrictions_csv.serialize(vec![
+        "id",
+        "restriction",
+        "mode",
+        "conditions",
+        "except",
+        "from_edge",
+        "via_node",
+        "via_edges",
+        "to_edge",
+    ])?;
+    for restriction in restrictions {
+        let conditions: Vec<String> = restriction
+            .conditions
+            .iter()
+            .map(|condition| condition.to_string())
+            .collect();
+        restrictions_csv.serialize((
+            restriction.id.0,
+            &restriction.restriction,
+            &restriction.mode,
+            conditions.join(" AND "),
+            &restriction.except,
+            &restriction.from_edge,
+            restriction.via_node.map(|node_id| node_id.0),
+            restriction.via_edges.join(";"),
+            &restriction.to_edge,
+        ))?;
+    }
+    restrictions_csv.flush()?;
+    Ok(())
+}
@@ -0,0 +1,27 @@
+use super::super::error::Error;
+use super::super::models::route::RouteMember;
+use std::path::Path;
+
+pub fn create_routes_csv(routes: &[RouteMember], routes_path: &Path) -> Result<(), Error> {
+    let mut routes_csv = csv::Writer::from_path(routes_path)?;
+    routes_csv.serialize(vec![
+        "edge_id",
+        "relation_id",
+        "route",
+        "ref",
+        "name",
+        "operator",
+    ])?;
+    for route in routes {
+        routes_csv.serialize((
+            &route.edge_id,
+            route.relation_id.0,
+            &route.route,
+            &route.reference,
+            &route.name,
+            &route.operator,
+        ))?;
+    }
+    routes_csv.flush()?;
+    Ok(())
+}
@@ -1,37 +1,291 @@
-use super::super::models::{edge::Edge, node::Node};
-use super::{edges, geohashes, nodes};
-use crate::ProfileType;
+use super::super::components::Components;
+use super::super::error::Error;
+use super::super::models::{edge::Edge, node::Node, restriction::Restriction, route::RouteMember};
+use super::super::profile::Profile;
+use super::super::sink::{EdgeSink, NodeSink};
+use super::edges::EdgesCsv;
+use super::geohashes::{self, GeohashCounts};
+use super::nodes::NodesCsv;
+use super::{geojson, parquet, restrictions, routes};
+use std::path::{Path, PathBuf};
 
-pub struct Writer {
+pub struct Writer<P> {
     nodes: Vec<Node>,
-    edges: Vec<Edge>,
-    profile_type: ProfileType,
+    edges: Vec<Edge<P>>,
+    profile: P,
+    restrictions: Option<Vec<Restriction>>,
+    routes: Option<Vec<RouteMember>>,
+    components: Option<Components>,
+    output_dir: PathBuf,
+    prefix: String,
 }
 
-impl Writer {
-    pub fn new(nodes: Vec<Node>, edges: Vec<Edge>, profile_type: ProfileType) -> Writer {
+impl<P: Profile> Writer<P> {
+    pub fn new(nodes: Vec<Node>, edges: Vec<Edge<P>>, profile: P) -> Writer<P> {
         Writer {
-            nodes: nodes,
-            edges: edges,
-            profile_type: profile_type,
+            nodes,
+            edges,
+            profile,
+            restrictions: None,
+            routes: None,
+            components: None,
+            output_dir: PathBuf::from("."),
+            prefix: String::new(),
         }
     }
 
-    pub fn to_csv(&self) {
-        let geohash_precision: u8 = 28;
+    // Turn restrictions written in restrictions.csv, whatever the format of the graph
+    pub fn restrictions(mut self, restrictions: Vec<Restriction>) -> Self {
+        self.restrictions = Some(restrictions);
+        self
+    }
+
+    // Route memberships of the edges written in routes.csv, whatever the format of the graph
+    pub fn routes(mut self, routes: Vec<RouteMember>) -> Self {
+        self.routes = Some(routes);
+        self
+    }
+
+    // Connected components written as component_id columns of the edges
+    pub fn components(mut self, components: Components) -> Self {
+        self.components = Some(components);
+        self
+    }
+
+    // Directory where the files are written, it is created if needed
+    pub fn output_dir<D: AsRef<Path>>(mut self, output_dir: D) -> Self {
+        self.output_dir = output_dir.as_ref().to_path_buf();
+        self
+    }
+
+    // Prefix prepended to every file name, e.g. "berlin-" gives "berlin-nodes.csv"
+    pub fn prefix(mut self, prefix: &str) -> Self {
+        self.prefix = prefix.to_string();
+        self
+    }
+
+    fn path(&self, file_name: &str) -> PathBuf {
+        output_path(&self.output_dir, &self.prefix, file_name)
+    }
+
+    // Writes restrictions.csv and routes.csv when they were given, and adds their paths
+    // They are tables without geometry, so they are written as csv for the other formats too
+    fn write_relations(&self, paths: &mut Vec<PathBuf>) -> Result<(), Error> {
+        if let Some(restrictions) = &self.restrictions {
+            let path = self.path("restrictions.csv");
+            restrictions::create_restrictions_csv(restrictions, &path)?;
+            paths.push(path);
+        }
+        if let Some(routes) = &self.routes {
+            let path = self.path("routes.csv");
+            routes::create_routes_csv(routes, &path)?;
+            paths.push(path);
+        }
+        Ok(())
+    }
+
+    // Writes the csv files and returns their paths
+    pub fn to_csv(&self) -> Result<Vec<PathBuf>, Error> {
+        let mut sink = CsvSink::create(
+            &self.profile,
+            self.components.as_ref(),
+            &self.output_dir,
+            &self.prefix,
+        )?;
+        for node in &self.nodes {
+            sink.nodes.write(node)?;
+        }
+        sink.nodes.finish()?;
+        for edge in &self.edges {
+            sink.write_edge(edge)?;
+        }
+        sink.finish_edges()?;
+        let mut paths = sink.finish()?;
+        self.write_relations(&mut paths)?;
+        Ok(paths)
+    }
+
+    // Writes the nodes and edges as GeoJSON FeatureCollections and returns the paths of the files
+    pub fn to_geojson(&self) -> Result<Vec<PathBuf>, Error> {
+        std::fs::create_dir_all(&self.output_dir)?;
+        let nodes_path = self.path("nodes.geojson");
+        let edges_path = self.path("edges.geojson");
+
+        geojson::create_nodes_geojson(&self.nodes, &self.profile, &nodes_path)?;
+        geojson::create_edges_geojson(&self.edges, self.components.as_ref(), &edges_path)?;
+
+        let mut paths = vec![nodes_path, edges_path];
+        self.write_relations(&mut paths)?;
+        Ok(paths)
+    }
+
+    // Writes the nodes and edges as GeoParquet files and returns the paths of the files
+    pub fn to_parquet(&self) -> Result<Vec<PathBuf>, Error> {
+        std::fs::create_dir_all(&self.output_dir)?;
+        let nodes_path = self.path("nodes.parquet");
+        let edges_path = self.path("edges.parquet");
+
+        parquet::create_nodes_parquet(&self.nodes, &self.profile, &nodes_path)?;
+        parquet::create_edges_parquet(
+            &self.edges,
+            &self.profile,
+            self.components.as_ref(),
+            &edges_path,
+        )?;
+
+        let mut paths = vec![nodes_path, edges_path];
+        self.write_relations(&mut paths)?;
+        Ok(paths)
+    }
+}
+
+// Path of an output file, with the prefix prepended to its name
+fn output_path(output_dir: &Path, prefix: &str, file_name: &str) -> PathBuf {
+    output_dir.join(format!("{}{}", prefix, file_name))
+}
+
+const GEOHASH_PRECISION: u8 = 28;
+
+// Files written under a temporary name, renamed once everything was written
+// The files still pending when it is dropped are removed, so a failed read leaves no partial files
+#[derive(Default)]
+struct TempFiles {
+    // Temporary path and final path
+    pending: Vec<(PathBuf, PathBuf)>,
+}
+
+impl TempFiles {
+    // Temporary path of the file, next to it
+    fn add(&mut self, path: &Path) -> PathBuf {
+        let mut file_name = path.file_name().unwrap_or_default().to_os_string();
+        file_name.push(".tmp");
+        let temp = path.with_file_name(file_name);
+        self.pending.push((temp.clone(), path.to_path_buf()));
+        temp
+    }
+
+    fn persist(&mut self) -> Result<(), Error> {
+        for (temp, path) in std::mem::take(&mut self.pending) {
+            std::fs::rename(temp, path)?;
+        }
+        Ok(())
+    }
+}
 
-        let mut geohashes: Vec<String> = Vec::new();
-        for edge in self.edges.iter() {
-            let mut edge_geohashes = edge.get_geohashes(geohash_precision);
-            geohashes.append(&mut edge_geohashes);
+impl Drop for TempFiles {
+    fn drop(&mut self) {
+        for (temp, _) in &self.pending {
+            let _ = std::fs::remove_file(temp);
         }
+    }
+}
+
+// CsvSink writes nodes.csv and edges.csv while the graph is read, see Reader::read_into
+// The files get their names in finish, a read failing before leaves the output directory untouched
+pub struct CsvSink<'a, P> {
+    nodes: NodesCsv<P>,
+    edges: EdgesCsv<'a>,
+    geohashes: GeohashCounts,
+    geohashes_path: PathBuf,
+    restrictions_path: PathBuf,
+    routes_path: PathBuf,
+    temp_files: TempFiles,
+    paths: Vec<PathBuf>,
+}
+
+impl<'a, P: Profile> CsvSink<'a, P> {
+    // Creates the files in the directory, with the prefix prepended to their names
+    pub fn new<D: AsRef<Path>>(profile: &P, output_dir: D, prefix: &str) -> Result<Self, Error> {
+        CsvSink::create(profile, None, output_dir.as_ref(), prefix)
+    }
+
+    fn create(
+        profile: &P,
+        components: Option<&'a Components>,
+        output_dir: &Path,
+        prefix: &str,
+    ) -> Result<Self, Error> {
+        std::fs::create_dir_all(output_dir)?;
+        let path = |file_name: &str| output_path(output_dir, prefix, file_name);
+        let nodes_path = path("nodes.csv");
+        let edges_path = path("edges.csv");
+        let geohashes_path = path("geohashes.csv");
+        let mut temp_files = TempFiles::default();
+        Ok(CsvSink {
+            nodes: NodesCsv::new(profile, &temp_files.add(&nodes_path))?,
+            edges: EdgesCsv::new(profile, components, &temp_files.add(&edges_path))?,
+            geohashes: GeohashCounts::new(),
+            geohashes_path: temp_files.add(&geohashes_path),
+            restrictions_path: path("restrictions.csv"),
+            routes_path: path("routes.csv"),
+            temp_files,
+            paths: vec![nodes_path, edges_path, geohashes_path],
+        })
+    }
+
+    fn write_edge(&mut self, edge: &Edge<P>) -> Result<(), Error> {
+        geohashes::count_geohashes(&mut self.geohashes, &edge.get_geohashes(GEOHASH_PRECISION));
+        self.edges.write(edge)
+    }
+
+    // Writes the turn restrictions in restrictions.csv
+    pub fn restrictions(&mut self, restrictions: &[Restriction]) -> Result<(), Error> {
+        let temp = self.temp_files.add(&self.restrictions_path);
+        restrictions::create_restrictions_csv(restrictions, &temp)?;
+        self.paths.push(self.restrictions_path.clone());
+        Ok(())
+    }
+
+    // Writes the route memberships of the edges in routes.csv
+    pub fn routes(&mut self, routes: &[RouteMember]) -> Result<(), Error> {
+        let temp = self.temp_files.add(&self.routes_path);
+        routes::create_routes_csv(routes, &temp)?;
+        self.paths.push(self.routes_path.clone());
+        Ok(())
+    }
+
+    // Gives the written files their names and returns their paths
+    pub fn finish(self) -> Result<Vec<PathBuf>, Error> {
+        let CsvSink {
+            nodes,
+            edges,
+            mut temp_files,
+            paths,
+            ..
+        } = self;
+        // The files are closed before being renamed
+        drop(nodes);
+        drop(edges);
+        temp_files.persist()?;
+        Ok(paths)
+    }
+}
 
-        nodes::create_nodes_csv(self.nodes.clone());
-        edges::create_edges_csv(self.edges.clone(), self.profile_type);
-        geohashes::create_geohashes_csv(geohashes, geohash_precision);
+impl<P: Profile> NodeSink for CsvSink<'_, P> {
+    fn push_node(&mut self, node: Node) -> Result<(), Error> {
+        self.nodes.write(&node)
+    }
+
+    fn finish_nodes(&mut self) -> Result<(), Error> {
+        self.nodes.finish()
+    }
+}
+
+impl<P: Profile> EdgeSink<P> for CsvSink<'_, P> {
+    fn push_edge(&mut self, edge: Edge<P>) -> Result<(), Error> {
+        self.write_edge(&edge)
+    }
+
+    fn finish_edges(&mut self) -> Result<(), Error> {
+        self.edges.finish()?;
+        geohashes::create_geohashes_csv(&self.geohashes, GEOHASH_PRECISION, &self.geohashes_path)
     }
 }
 
-pub fn write(nodes: Vec<Node>, edges: Vec<Edge>, profile_type: ProfileType) {
-    Writer::new(nodes, edges, profile_type).to_csv();
+pub fn write<P: Profile>(
+    nodes: Vec<Node>,
+    edges: Vec<Edge<P>>,
+    profile: P,
+) -> Result<Vec<PathBuf>, Error> {
+    Writer::new(nodes, edges, profile).to_csv()
 }
@@ -0,0 +1,92 @@
+mod common;
+
+use common::*;
+use osm4routing::road::edge_properties::Access;
+use osm4routing::*;
+
+fn graph(profile: RoadEdgeProperties) -> Graph<RoadEdgeProperties> {
+    let (nodes, edges) = read(&test_data("access.osm"), profile).unwrap();
+    Graph::new(nodes, edges)
+}
+
+fn route_ids(route: &Route) -> Vec<&str> {
+    route
+        .edges
+        .iter()
+        .map(|edge| edge.edge_id.as_str())
+        .collect()
+}
+
+fn edge(graph: &Graph<RoadEdgeProperties>, id: &str) -> usize {
+    graph.edges.iter().position(|edge| edge.id == id).unwrap()
+}
+
+#[test]
+fn private_roads_are_denied_by_default() {
+    let graph = graph(RoadEdgeProperties::default());
+    let private = edge(&graph, "10-0");
+    assert_eq!(graph.edges[private].properties.car_access, Access::Private);
+    for mode in [Mode::Foot, Mode::Bike, Mode::Car] {
+        assert!(!graph.accessible(private, Some(mode)), "{mode:?}");
+        assert_eq!(graph.edges[private].travel_time(mode), None);
+        // The long way round
+        let route = graph.dijkstra(NodeId(1), NodeId(2), Some(mode)).unwrap();
+        assert!(!route_ids(&route).contains(&"10-0"), "{mode:?}");
+    }
+    // Without mode, every edge can be used
+    let route = graph.dijkstra(NodeId(1), NodeId(2), None).unwrap();
+    assert_eq!(route_ids(&route), ["10-0"]);
+
+    // Nor in the components of the modes
+    let components = Components::compute(&graph.edges, &RoadEdgeProperties::default());
+    assert_eq!(components.row("10-0"), [None, None, None]);
+    assert_eq!(components.row("11-0"), [Some(0), Some(0), Some(0)]);
+}
+
+#[test]
+fn private_roads_can_be_allowed() {
+    let graph = graph(RoadEdgeProperties::default().allow_private(true));
+    let private = edge(&graph, "10-0");
+    assert!(graph.accessible(private, Some(Mode::Car)));
+    // Still a restricted road, its cost is increased
+    let travel_time = graph.edges[private].travel_time(Mode::Car).unwrap();
+    let arc = graph.arc(private, true);
+    assert!((graph.cost(&arc, Some(Mode::Car)).unwrap() - 5. * travel_time).abs() < 1e-6);
+
+    // Used when there is no other way
+    let (nodes, mut edges) = read(
+        &test_data("access.osm"),
+        RoadEdgeProperties::default().allow_private(true),
+    )
+    .unwrap();
+    edges.retain(|edge| edge.id == "10-0");
+    let graph = Graph::new(nodes, edges);
+    let route = graph
+        .dijkstra(NodeId(1), NodeId(2), Some(Mode::Car))
+        .unwrap();
+    assert_eq!(route_ids(&route), ["10-0"]);
+}
+
+#[test]
+fn destination_roads_cost_more() {
+    let graph = graph(RoadEdgeProperties::default());
+    let destination = edge(&graph, "13-0");
+    assert_eq!(
+        graph.edges[destination].properties.car_access,
+        Access::Destination
+    );
+    assert!(graph.accessible(destination, Some(Mode::Car)));
+    let travel_time = graph.edges[destination].travel_time(Mode::Car).unwrap();
+    let arc = graph.arc(destination, true);
+    assert!((graph.cost(&arc, Some(Mode::Car)).unwrap() - 5. * travel_time).abs() < 1e-6);
+
+    // Cars go round by 5 rather than through the 111 m of 13, pedestrians go straight
+    let route = graph
+        .dijkstra(NodeId(3), NodeId(2), Some(Mode::Car))
+        .unwrap();
+    assert_eq!(route_ids(&route), ["15-0", "14-0"]);
+    let route = graph
+        .dijkstra(NodeId(3), NodeId(2), Some(Mode::Foot))
+        .unwrap();
+    assert_eq!(route_ids(&route), ["13-0"]);
+}
@@ -0,0 +1,70 @@
+mod common;
+
+use common::*;
+use osm4routing::node::Node;
+use osm4routing::*;
+
+#[test]
+fn clip_keeps_whole_ways() {
+    let area = ClipArea::bbox(2.003, 47.999, 2.005, 48.002).unwrap();
+    let (_, edges) = Reader::new(RoadEdgeProperties::default())
+        .clip(area, ClipMode::KeepWhole)
+        .read(NETWORK)
+        .unwrap();
+    // Without 102 and 104, the nodes 2 and 5 are no longer junctions
+    assert_eq!(edge_ids(&edges), vec!["100-0", "101-0", "103-0"]);
+    let way = edges.iter().find(|edge| edge.id == "100-0").unwrap();
+    assert_eq!((way.source, way.target), (NodeId(1), NodeId(3)));
+}
+
+#[test]
+fn clip_cuts_ways_at_the_boundary() {
+    let area = ClipArea::bbox(2.003, 47.999, 2.005, 48.002).unwrap();
+    let (nodes, edges) = Reader::new(RoadEdgeProperties::default())
+        .clip(area.clone(), ClipMode::Cut)
+        .read(NETWORK)
+        .unwrap();
+    assert_eq!(edges.len(), 3);
+    for edge in &edges {
+        for coord in &edge.geometry {
+            assert!(coord.lon >= 2.003 - 1e-9, "{} leaves the area", edge.id);
+        }
+    }
+    // The nodes at the crossings are numbered below the ids of the file
+    let new_nodes: Vec<&Node> = nodes.iter().filter(|node| node.id.0 < 1).collect();
+    assert_eq!(new_nodes.len(), 2);
+    for node in new_nodes {
+        assert!((node.coord.lon - 2.003).abs() < 1e-9);
+    }
+}
+
+// The segment 1-2 of 100 crosses the area without any node inside of it
+const CROSSED: [f64; 4] = [2.0005, 47.9995, 2.0015, 48.0005];
+
+#[test]
+fn clip_keeps_ways_crossing_the_area_without_a_node_inside() {
+    let [min_lon, min_lat, max_lon, max_lat] = CROSSED;
+    let area = ClipArea::bbox(min_lon, min_lat, max_lon, max_lat).unwrap();
+    let (_, edges) = Reader::new(RoadEdgeProperties::default())
+        .clip(area, ClipMode::KeepWhole)
+        .read(NETWORK)
+        .unwrap();
+    assert_eq!(edge_ids(&edges), vec!["100-0"]);
+    assert_eq!(edges[0].geometry.len(), 3);
+}
+
+#[test]
+fn clip_cuts_ways_crossing_the_area_without_a_node_inside() {
+    let [min_lon, min_lat, max_lon, max_lat] = CROSSED;
+    let area = ClipArea::bbox(min_lon, min_lat, max_lon, max_lat).unwrap();
+    let (nodes, edges) = Reader::new(RoadEdgeProperties::default())
+        .clip(area, ClipMode::Cut)
+        .read(NETWORK)
+        .unwrap();
+    assert_eq!(edges.len(), 1);
+    let lons: Vec<f64> = edges[0].geometry.iter().map(|coord| coord.lon).collect();
+    assert_eq!(lons.len(), 2);
+    assert!((lons[0] - 2.0005).abs() < 1e-9 && (lons[1] - 2.0015).abs() < 1e-9);
+    assert_eq!(nodes.len(), 2);
+    assert!(nodes.iter().all(|node| node.id.0 < 1));
+}
@@ -0,0 +1,34 @@
+// Helpers shared by the integration tests, each test file only uses some of them
+#![allow(dead_code)]
+
+use osm4routing::edge::Edge;
+use osm4routing::node::Node;
+use std::path::PathBuf;
+
+pub const NETWORK: &str = "src/osm4routing/test_data/network.osm";
+
+pub fn test_data(file: &str) -> String {
+    format!("src/osm4routing/test_data/{}", file)
+}
+
+// Sorted ids of the edges
+pub fn edge_ids<P>(edges: &[Edge<P>]) -> Vec<&str> {
+    let mut ids: Vec<&str> = edges.iter().map(|edge| edge.id.as_str()).collect();
+    ids.sort_unstable();
+    ids
+}
+
+// Sorted ids of the nodes
+pub fn node_ids(nodes: &[Node]) -> Vec<i64> {
+    let mut ids: Vec<i64> = nodes.iter().map(|node| node.id.0).collect();
+    ids.sort_unstable();
+    ids
+}
+
+// Empty directory in the temporary directory, unique for the test
+pub fn output_dir(test: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("osm4routing-{}-{}", test, std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    dir
+}
@@ -0,0 +1,26 @@
+mod common;
+
+use common::*;
+use osm4routing::*;
+
+#[test]
+fn components_drop_isolated_pieces() {
+    let profile = RoadEdgeProperties::default();
+    let (nodes, edges) = read(NETWORK, profile.clone()).unwrap();
+    let components = Components::compute(&edges, &profile);
+    let main = components.row("100-0");
+    assert_eq!(components.row("101-1"), main);
+    assert_ne!(components.row("200-0"), main);
+
+    let (nodes, edges) = components.drop_small(nodes, edges, Some(2), None);
+    assert_eq!(node_ids(&nodes), vec![1, 2, 3, 4, 5, 6]);
+    assert!(!edge_ids(&edges).contains(&"200-0"));
+    assert_eq!(edges.len(), 7);
+
+    // The isolated way is about 150 m long
+    let (nodes, edges) = read(NETWORK, profile.clone()).unwrap();
+    let (_, kept) = components.drop_small(nodes.clone(), edges.clone(), None, Some(100.));
+    assert_eq!(kept.len(), 8);
+    let (_, kept) = components.drop_small(nodes, edges, None, Some(200.));
+    assert_eq!(kept.len(), 7);
+}
@@ -0,0 +1,43 @@
+mod common;
+
+use common::*;
+use osm4routing::*;
+
+fn time(day: u32, hour: u32) -> chrono::NaiveDateTime {
+    chrono::NaiveDate::from_ymd_opt(2024, 6, day)
+        .unwrap()
+        .and_hms_opt(hour, 0, 0)
+        .unwrap()
+}
+
+#[test]
+fn ways_opened_by_a_conditional_restriction_are_kept() {
+    let (_, edges) = read(&test_data("conditional.osm"), RoadEdgeProperties::default()).unwrap();
+    // The way 2 stays closed whatever the time
+    assert_eq!(edge_ids(&edges), ["1-0"]);
+    let edge = &edges[0];
+    assert!(!edge.properties.accessible());
+    for mode in [Mode::Foot, Mode::Bike, Mode::Car] {
+        // Monday 3 June 2024
+        assert!(edge.accessible_at(mode, &time(3, 10)), "{mode:?}");
+        assert!(!edge.accessible_at(mode, &time(3, 20)), "{mode:?}");
+        assert!(!edge.accessible_at(mode, &time(9, 10)), "{mode:?}");
+    }
+    assert!(edge.travel_time_at(Mode::Car, &time(3, 10)).is_some());
+}
+
+#[test]
+fn rule_profiles_reject_conditional_keys() {
+    let definition = r#"
+        [[properties]]
+        name = "access"
+
+        [[rules]]
+        key = "access:conditional"
+        set = { access = "$value" }
+    "#;
+    match RuleProfile::from_toml(definition) {
+        Err(Error::Profile(message)) => assert!(message.contains("access:conditional")),
+        _ => panic!("conditional keys must be rejected"),
+    }
+}
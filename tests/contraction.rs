@@ -0,0 +1,29 @@
+mod common;
+
+use common::*;
+use osm4routing::*;
+use std::collections::HashSet;
+
+#[test]
+fn contraction_keeps_restriction_nodes() {
+    let (nodes, edges, restrictions) = Reader::new(RoadEdgeProperties::default())
+        .contract(true)
+        .read_with_restrictions(NETWORK)
+        .unwrap();
+    // 3 and 6 are removed, 1 and 4 only join two edges but are used by the restriction
+    assert_eq!(node_ids(&nodes), vec![1, 2, 4, 5, 10, 11]);
+    assert_eq!(
+        edge_ids(&edges),
+        vec!["100-0", "101-0", "101-1", "102-0", "104-0", "200-0"]
+    );
+    let merged = edges.iter().find(|edge| edge.id == "101-1").unwrap();
+    assert_eq!(merged.osm_ids, vec![WayId(101), WayId(103), WayId(100)]);
+    assert_eq!((merged.source, merged.target), (NodeId(5), NodeId(2)));
+    assert_eq!(merged.geometry.len(), 4);
+
+    let edges: HashSet<&str> = edges.iter().map(|edge| edge.id.as_str()).collect();
+    for restriction in &restrictions {
+        assert!(edges.contains(restriction.from_edge.as_str()));
+        assert!(edges.contains(restriction.to_edge.as_str()));
+    }
+}
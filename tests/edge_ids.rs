@@ -0,0 +1,25 @@
+mod common;
+
+use common::*;
+use osm4routing::*;
+
+#[test]
+fn edge_ids_from_nodes() {
+    let (_, edges) = Reader::new(RoadEdgeProperties::default())
+        .edge_ids(EdgeIdScheme::Nodes)
+        .read(NETWORK)
+        .unwrap();
+    assert!(edge_ids(&edges).contains(&"100-1-2"));
+    assert!(edge_ids(&edges).contains(&"104-2-5"));
+}
+
+#[test]
+fn contraction_renames_merged_edges_with_nodes_scheme() {
+    let (_, edges) = Reader::new(RoadEdgeProperties::default())
+        .contract(true)
+        .edge_ids(EdgeIdScheme::Nodes)
+        .read(NETWORK)
+        .unwrap();
+    assert!(edge_ids(&edges).contains(&"101-5-2"));
+    assert!(edge_ids(&edges).contains(&"100-1-2"));
+}
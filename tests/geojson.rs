@@ -0,0 +1,77 @@
+mod common;
+
+use common::*;
+use osm4routing::*;
+use serde_json::Value;
+
+fn features(path: &std::path::Path) -> Vec<Value> {
+    let content = std::fs::read_to_string(path).unwrap();
+    let collection: Value = serde_json::from_str(&content).unwrap();
+    assert_eq!(collection["type"], "FeatureCollection");
+    collection["features"].as_array().unwrap().clone()
+}
+
+#[test]
+fn nodes_and_edges_as_feature_collections() {
+    let (nodes, edges) = read(NETWORK, RoadEdgeProperties::default()).unwrap();
+    let dir = output_dir("geojson");
+    let paths = Writer::new(nodes, edges, RoadEdgeProperties::default())
+        .output_dir(&dir)
+        .to_geojson()
+        .unwrap();
+    assert_eq!(
+        paths,
+        [dir.join("nodes.geojson"), dir.join("edges.geojson")]
+    );
+
+    let nodes = features(&paths[0]);
+    assert_eq!(nodes.len(), 8);
+    let node = nodes
+        .iter()
+        .find(|node| node["properties"]["id"] == 5)
+        .unwrap();
+    assert_eq!(node["geometry"]["type"], "Point");
+    assert_eq!(
+        node["geometry"]["coordinates"],
+        serde_json::json!([2.002, 48.001])
+    );
+
+    let edges = features(&paths[1]);
+    assert_eq!(edges.len(), 8);
+    let edge = edges
+        .iter()
+        .find(|edge| edge["properties"]["id"] == "104-0")
+        .unwrap();
+    assert_eq!(edge["geometry"]["type"], "LineString");
+    assert_eq!(
+        edge["geometry"]["coordinates"],
+        serde_json::json!([[2.002, 48.0], [2.002, 48.001]])
+    );
+    let properties = &edge["properties"];
+    assert_eq!(properties["osm_id"], 104);
+    assert_eq!(
+        (properties["source"].as_i64(), properties["target"].as_i64()),
+        (Some(2), Some(5))
+    );
+    assert_eq!(properties["car_forward"], "Residential");
+    assert_eq!(properties["car_backward"], "Forbidden");
+    assert!(properties["length"].as_f64().unwrap() > 100.);
+}
+
+#[test]
+fn railway_nodes_with_their_properties() {
+    let profile = RailwayEdgeProperties::default();
+    let (nodes, edges) = read(&test_data("switches.osm"), profile.clone()).unwrap();
+    let dir = output_dir("geojson-railway");
+    let paths = Writer::new(nodes, edges, profile)
+        .output_dir(&dir)
+        .to_geojson()
+        .unwrap();
+    let nodes = features(&paths[0]);
+    let signal = nodes
+        .iter()
+        .find(|node| node["properties"]["id"] == 7)
+        .unwrap();
+    assert_eq!(signal["properties"]["railway_type"], "Signal");
+    assert_eq!(signal["properties"]["ref"], "S7");
+}
@@ -0,0 +1,135 @@
+mod common;
+
+use common::*;
+use osm4routing::*;
+
+#[test]
+fn infrastructure_nodes_are_vertices() {
+    let (nodes, edges) =
+        read(&test_data("switches.osm"), RailwayEdgeProperties::default()).unwrap();
+    // The signal 7 splits way 11, the station 9 isn't on a track
+    assert_eq!(node_ids(&nodes), vec![1, 2, 3, 4, 5, 6, 7]);
+    assert_eq!(
+        edge_ids(&edges),
+        vec!["10-0", "11-0", "11-1", "12-0", "13-0", "14-0"]
+    );
+    for node in &nodes {
+        assert_eq!(node.is_infrastructure(), [2, 5, 7].contains(&node.id.0));
+    }
+}
+
+#[test]
+fn infrastructure_columns() {
+    let profile = RailwayEdgeProperties::default();
+    let (nodes, _) = read(&test_data("switches.osm"), profile.clone()).unwrap();
+    let columns = profile.node_columns();
+    assert_eq!(
+        columns,
+        [
+            "railway_type",
+            "name",
+            "ref",
+            "switch_type",
+            "signal_direction",
+            "crossing_barrier"
+        ]
+    );
+    let row = |id: i64| -> Vec<String> {
+        let node = nodes.iter().find(|node| node.id.0 == id).unwrap();
+        profile
+            .node_row(node)
+            .iter()
+            .map(|value| value.to_string())
+            .collect()
+    };
+    assert_eq!(row(2), ["Switch", "", "W1", "default", "", ""]);
+    assert_eq!(row(7), ["Signal", "", "S7", "", "forward", ""]);
+    assert_eq!(row(1), ["", "", "", "", "", ""]);
+
+    // The road profile has no node properties
+    assert!(RoadEdgeProperties::default().node_columns().is_empty());
+}
+
+// Profile of another crate, with its own node properties
+#[derive(Clone, Default, PartialEq)]
+struct Tracks {
+    rail: bool,
+}
+
+#[derive(Default)]
+struct Signal {
+    reference: String,
+}
+
+impl NodeProps for Signal {
+    fn is_infrastructure(&self) -> bool {
+        true
+    }
+
+    fn row(&self) -> Vec<PropertyValue> {
+        vec![PropertyValue::Text(self.reference.clone())]
+    }
+}
+
+impl Profile for Tracks {
+    type NodeProperties = Signal;
+
+    fn update_with_str(&mut self, key: &str, val: &str) {
+        self.rail |= key == "railway" && val == "rail";
+    }
+
+    fn keep(&self) -> bool {
+        self.rail
+    }
+
+    fn columns(&self) -> Vec<String> {
+        Vec::new()
+    }
+
+    fn row(&self) -> Vec<PropertyValue> {
+        Vec::new()
+    }
+
+    fn node_properties(&self, tags: &Tags) -> Option<Signal> {
+        (tags.get("railway").map(|value| value.as_str()) == Some("signal")).then(|| Signal {
+            reference: tags
+                .get("ref")
+                .map(|value| value.to_string())
+                .unwrap_or_default(),
+        })
+    }
+
+    fn node_columns(&self) -> Vec<String> {
+        vec!["signal".to_string()]
+    }
+}
+
+#[test]
+fn node_properties_of_another_profile() {
+    let (nodes, edges) = read(&test_data("switches.osm"), Tracks::default()).unwrap();
+    // The signal 7 still splits way 11
+    assert_eq!(
+        edge_ids(&edges),
+        vec!["10-0", "11-0", "11-1", "12-0", "13-0", "14-0"]
+    );
+    for node in &nodes {
+        assert_eq!(node.is_infrastructure(), node.id.0 == 7);
+    }
+
+    let dir = output_dir("node-properties");
+    Writer::new(nodes, edges, Tracks::default())
+        .output_dir(&dir)
+        .to_csv()
+        .unwrap();
+    let mut csv = csv::Reader::from_path(dir.join("nodes.csv")).unwrap();
+    assert_eq!(csv.headers().unwrap().iter().next_back(), Some("signal"));
+    let signals: Vec<String> = csv
+        .records()
+        .map(|record| record.unwrap().iter().next_back().unwrap().to_string())
+        .collect();
+    assert_eq!(
+        signals.iter().filter(|signal| !signal.is_empty()).count(),
+        1
+    );
+    assert!(signals.contains(&"S7".to_string()));
+}
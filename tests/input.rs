@@ -0,0 +1,95 @@
+mod common;
+
+use common::*;
+use osm4routing::*;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+// Edge id, source, target and geometry
+type EdgeSummary = (String, i64, i64, Vec<(f64, f64)>);
+
+// Edges with their extremities and geometry, to compare the graphs read from several formats
+fn graph(path: &Path) -> Vec<EdgeSummary> {
+    let (_, edges) = read(path.to_str().unwrap(), RailwayEdgeProperties::default()).unwrap();
+    let mut result: Vec<_> = edges
+        .into_iter()
+        .map(|edge| {
+            let geometry = edge.geometry.iter().map(|c| (c.lon, c.lat)).collect();
+            (edge.id, edge.source.0, edge.target.0, geometry)
+        })
+        .collect();
+    result.sort_by(|a, b| a.0.cmp(&b.0));
+    result
+}
+
+fn gzip(content: &[u8], path: &Path) {
+    let file = std::fs::File::create(path).unwrap();
+    let mut encoder = flate2::write::GzEncoder::new(file, flate2::Compression::default());
+    encoder.write_all(content).unwrap();
+    encoder.finish().unwrap();
+}
+
+fn bzip2(content: &[u8], path: &Path) {
+    let file = std::fs::File::create(path).unwrap();
+    let mut encoder = bzip2::write::BzEncoder::new(file, bzip2::Compression::default());
+    encoder.write_all(content).unwrap();
+    encoder.finish().unwrap();
+}
+
+// The railway test data as XML, gzip and bzip2 files, with and without extension
+fn copies(dir: &Path) -> Vec<(PathBuf, InputFormat)> {
+    let xml = std::fs::read(test_data("railway.osm")).unwrap();
+    let pbf = std::fs::read(test_data("railway.osm.pbf")).unwrap();
+    let mut files = Vec::new();
+    for (name, format) in [
+        ("railway.osm.gz", InputFormat::XmlGz),
+        ("railway.osm.bz2", InputFormat::XmlBz2),
+        ("railway.xml", InputFormat::Xml),
+        ("railway.osm.pbf", InputFormat::Pbf),
+        ("gz-without-extension", InputFormat::XmlGz),
+        ("bz2-without-extension", InputFormat::XmlBz2),
+        ("xml-without-extension", InputFormat::Xml),
+        ("pbf-without-extension", InputFormat::Pbf),
+    ] {
+        let path = dir.join(name);
+        match format {
+            InputFormat::XmlGz => gzip(&xml, &path),
+            InputFormat::XmlBz2 => bzip2(&xml, &path),
+            InputFormat::Xml => std::fs::write(&path, &xml).unwrap(),
+            InputFormat::Pbf => std::fs::write(&path, &pbf).unwrap(),
+        }
+        files.push((path, format));
+    }
+    files
+}
+
+#[test]
+fn detect_the_format() {
+    let dir = output_dir("input-detect");
+    for (path, format) in copies(&dir) {
+        assert_eq!(InputFormat::detect(&path).unwrap(), format, "{:?}", path);
+    }
+    assert_eq!(
+        InputFormat::detect(Path::new("planet.OSM.PBF")).unwrap(),
+        InputFormat::Pbf
+    );
+}
+
+#[test]
+fn every_format_gives_the_same_graph() {
+    let dir = output_dir("input-formats");
+    let expected = graph(Path::new(&test_data("railway.osm")));
+    assert_eq!(expected.len(), 3);
+    for (path, _) in copies(&dir) {
+        assert_eq!(graph(&path), expected, "{:?}", path);
+    }
+}
+
+#[test]
+fn invalid_xml() {
+    let dir = output_dir("input-invalid");
+    let path = dir.join("invalid.osm");
+    std::fs::write(&path, "<osm><node id='1' lat='x' lon='2'/></osm>").unwrap();
+    let result = read(path.to_str().unwrap(), RailwayEdgeProperties::default());
+    assert!(matches!(result, Err(Error::Xml(_))));
+}
@@ -0,0 +1,92 @@
+mod common;
+
+use common::*;
+use osm4routing::coord::Coord;
+use osm4routing::*;
+
+fn graph() -> Graph<RoadEdgeProperties> {
+    let (nodes, edges) = read(NETWORK, RoadEdgeProperties::default()).unwrap();
+    Graph::new(nodes, edges)
+}
+
+#[test]
+fn nodes_within_the_distance() {
+    let graph = graph();
+    // 100-0 is about 149 m long, 102-0 about 111 m
+    let isochrone = Isochrone::from_node(&graph, NodeId(1), 200., None).unwrap();
+    let mut reached: Vec<i64> = isochrone.nodes.iter().map(|node| node.id.0).collect();
+    reached.sort_unstable();
+    assert_eq!(reached, [1, 2, 4]);
+    assert!(isochrone.nodes.iter().all(|node| node.cost <= 200.));
+
+    // The edges leaving 2 and 4 are reached on the 51 m and 89 m left
+    let part = |id: &str| {
+        isochrone
+            .edges
+            .iter()
+            .find(|edge| edge.edge_id == id)
+            .unwrap_or_else(|| panic!("{id}"))
+    };
+    assert_eq!((part("100-0").start, part("100-0").end), (0., 1.));
+    let towards_5 = part("101-0");
+    assert!(towards_5.start == 0. && (towards_5.end - 89. / 149.).abs() < 0.01);
+    assert!(isochrone.edges.iter().all(|edge| edge.edge_id != "200-0"));
+
+    assert!(Isochrone::from_node(&graph, NodeId(42), 200., None).is_none());
+}
+
+#[test]
+fn hull_around_the_reached_network() {
+    let graph = graph();
+    let isochrone = Isochrone::from_node(&graph, NodeId(1), 300., Some(Mode::Foot)).unwrap();
+    let hull = isochrone.concave_hull(3).unwrap();
+    assert!(hull.len() >= 3);
+    let ring: Vec<[f64; 2]> = hull.iter().map(|c| [c.lon, c.lat]).collect();
+    for node in &isochrone.nodes {
+        let (x, y) = (node.coord.lon, node.coord.lat);
+        // Even-odd rule, the nodes on the boundary are kept by the small margin
+        let mut inside = false;
+        for i in 0..ring.len() {
+            let (a, b) = (ring[i], ring[(i + 1) % ring.len()]);
+            let on_boundary = ((x - a[0]) * (b[1] - a[1]) - (y - a[1]) * (b[0] - a[0])).abs()
+                < 1e-12
+                && (x - a[0]) * (x - b[0]) <= 1e-12
+                && (y - a[1]) * (y - b[1]) <= 1e-12;
+            if on_boundary {
+                inside = true;
+                break;
+            }
+            if (a[1] > y) != (b[1] > y) && x < a[0] + (y - a[1]) / (b[1] - a[1]) * (b[0] - a[0]) {
+                inside = !inside;
+            }
+        }
+        assert!(inside, "{:?}", node.id);
+    }
+
+    let geojson = isochrone.to_geojson(Some(3));
+    let features = geojson["features"].as_array().unwrap();
+    assert_eq!(features.len(), isochrone.edges.len() + 1);
+    let polygon = features.last().unwrap();
+    assert_eq!(polygon["geometry"]["type"], "Polygon");
+    let ring = polygon["geometry"]["coordinates"][0].as_array().unwrap();
+    assert_eq!(ring.first(), ring.last());
+}
+
+#[test]
+fn from_a_coordinate() {
+    let graph = graph();
+    // On 104, 11 m east of its middle, 104 being a oneway from 2 to 5
+    let coord = Coord {
+        lon: 2.00215,
+        lat: 48.0005,
+    };
+    let isochrone = Isochrone::from_coord(&graph, &coord, 20., Some(Mode::Car)).unwrap();
+    let mut reached: Vec<i64> = isochrone.nodes.iter().map(|node| node.id.0).collect();
+    reached.sort_unstable();
+    // About 55 m at 30 km/h to node 5, cars can't go back to node 2
+    assert_eq!(reached, [5]);
+    assert!(isochrone
+        .edges
+        .iter()
+        .any(|edge| edge.edge_id == "104-0" && edge.end == 1.));
+}
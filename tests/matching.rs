@@ -0,0 +1,134 @@
+mod common;
+
+use chrono::{NaiveDate, NaiveDateTime};
+use common::*;
+use osm4routing::coord::Coord;
+use osm4routing::*;
+
+fn graph() -> Graph<RoadEdgeProperties> {
+    let (nodes, edges) = read(NETWORK, RoadEdgeProperties::default()).unwrap();
+    Graph::new(nodes, edges)
+}
+
+fn matched_ids(matched: &MapMatch) -> Vec<&str> {
+    matched
+        .edges
+        .iter()
+        .map(|edge| edge.edge_id.as_str())
+        .collect()
+}
+
+fn trace(points: &[(f64, f64)]) -> Vec<TracePoint> {
+    points
+        .iter()
+        .enumerate()
+        .map(|(i, &(lon, lat))| TracePoint {
+            coord: Coord { lon, lat },
+            time: time(10 * i as u32),
+        })
+        .collect()
+}
+
+fn time(second: u32) -> NaiveDateTime {
+    NaiveDate::from_ymd_opt(2024, 6, 3)
+        .unwrap()
+        .and_hms_opt(12, 0, second)
+        .unwrap()
+}
+
+#[test]
+fn match_trace_along_the_network() {
+    let graph = graph();
+    // From 1 to 2 a few meters north of way 100, then down the oneway 104 towards 5
+    let trace = trace(&[
+        (2.0002, 48.00003),
+        (2.0010, 48.00003),
+        (2.0018, 48.00003),
+        (2.00203, 48.0004),
+        (2.00203, 48.0008),
+    ]);
+    let matched = match_trace(&graph, &trace, Some(Mode::Car), &MatchOptions::default());
+    assert!(matched.points.iter().all(|point| point.is_some()));
+    assert_eq!(matched_ids(&matched), vec!["100-0", "104-0"]);
+    assert!(matched.edges.iter().all(|edge| edge.forward));
+
+    // Far from every edge
+    let far = [TracePoint {
+        coord: Coord {
+            lon: 2.5,
+            lat: 48.5,
+        },
+        time: time(0),
+    }];
+    let matched = match_trace(&graph, &far, Some(Mode::Car), &MatchOptions::default());
+    assert!(matched.points[0].is_none());
+    assert!(matched.edges.is_empty());
+}
+
+// Tracks without any turn limit, to compare with the railway profile
+const FREE_TRACKS: &str = r#"
+    [[properties]]
+    name = "railway"
+
+    [[rules]]
+    key = "railway"
+    set = { railway = "$value" }
+
+    [[keep]]
+    property = "railway"
+    not = ""
+"#;
+
+#[test]
+fn trains_do_not_take_the_acute_branch_of_a_switch() {
+    // Eastwards on the upper track, then back westwards on the lower track: the only way is the
+    // crossover 12, taken in the opposite direction at the switch 5
+    let trace = trace(&[
+        (2.004, 48.0004),
+        (2.008, 48.0004),
+        (2.012, 48.0004),
+        (2.008, 48.0),
+        (2.004, 48.0),
+    ]);
+    let options = MatchOptions {
+        sigma: 2.,
+        ..Default::default()
+    };
+
+    let file = test_data("switches.osm");
+    let (nodes, edges) = read(&file, RuleProfile::from_toml(FREE_TRACKS).unwrap()).unwrap();
+    let matched = match_trace(&Graph::new(nodes, edges), &trace, None, &options);
+    assert_eq!(matched_ids(&matched), vec!["13-0", "12-0", "10-0"]);
+
+    let (nodes, edges) = read(&file, RailwayEdgeProperties::default()).unwrap();
+    let matched = match_trace(&Graph::new(nodes, edges), &trace, None, &options);
+    assert!(!matched_ids(&matched).contains(&"12-0"));
+    assert!(matched.points.iter().all(|point| point.is_some()));
+}
+
+#[test]
+fn duplicated_nodes_do_not_hide_the_acute_branch_of_a_switch() {
+    // Same trace, the crossover 12 ending by a node duplicated at the switch 5: its last segment
+    // has no direction, the bearing at the switch comes from the previous one
+    let trace = trace(&[
+        (2.004, 48.0004),
+        (2.008, 48.0004),
+        (2.012, 48.0004),
+        (2.008, 48.0),
+        (2.004, 48.0),
+    ]);
+    let options = MatchOptions {
+        sigma: 2.,
+        ..Default::default()
+    };
+
+    let (nodes, mut edges) =
+        read(&test_data("switches.osm"), RailwayEdgeProperties::default()).unwrap();
+    let crossover = edges.iter_mut().find(|edge| edge.id == "12-0").unwrap();
+    let switch = *crossover.geometry.last().unwrap();
+    crossover.geometry.push(switch);
+
+    let matched = match_trace(&Graph::new(nodes, edges), &trace, None, &options);
+    assert!(!matched_ids(&matched).contains(&"12-0"));
+    assert!(matched.points.iter().all(|point| point.is_some()));
+}
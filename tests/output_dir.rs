@@ -0,0 +1,77 @@
+mod common;
+
+use common::*;
+use osm4routing::*;
+
+#[test]
+fn files_in_the_output_directory_with_the_prefix() {
+    let (nodes, edges) = read(NETWORK, RoadEdgeProperties::default()).unwrap();
+    let dir = output_dir("output-dir").join("new");
+    let paths = Writer::new(nodes, edges, RoadEdgeProperties::default())
+        .output_dir(&dir)
+        .prefix("paris-")
+        .to_csv()
+        .unwrap();
+    let names: Vec<&str> = paths
+        .iter()
+        .map(|path| path.file_name().unwrap().to_str().unwrap())
+        .collect();
+    assert_eq!(
+        names,
+        ["paris-nodes.csv", "paris-edges.csv", "paris-geohashes.csv"]
+    );
+    for path in &paths {
+        assert_eq!(path.parent(), Some(dir.as_path()));
+        assert!(path.is_file());
+    }
+    let edges = std::fs::read_to_string(&paths[1]).unwrap();
+    assert_eq!(edges.lines().count(), 9);
+}
+
+#[test]
+fn relations_follow_the_prefix() {
+    let (nodes, edges, relations) = Reader::new(RoadEdgeProperties::default())
+        .read_with_relations(NETWORK)
+        .unwrap();
+    let dir = output_dir("relations-prefix");
+    let paths = Writer::new(nodes, edges, RoadEdgeProperties::default())
+        .restrictions(relations.restrictions)
+        .routes(relations.routes)
+        .output_dir(&dir)
+        .prefix("x-")
+        .to_csv()
+        .unwrap();
+    assert!(paths.contains(&dir.join("x-restrictions.csv")));
+    assert!(paths.contains(&dir.join("x-routes.csv")));
+    let restrictions = std::fs::read_to_string(dir.join("x-restrictions.csv")).unwrap();
+    assert_eq!(restrictions.lines().count(), 3);
+}
+
+#[test]
+fn geometry_column_of_each_profile() {
+    let header = |dir: &std::path::Path| {
+        let mut csv = csv::Reader::from_path(dir.join("edges.csv")).unwrap();
+        csv.headers()
+            .unwrap()
+            .iter()
+            .next_back()
+            .unwrap()
+            .to_string()
+    };
+    let (nodes, edges) = read(NETWORK, RoadEdgeProperties::default()).unwrap();
+    let road = output_dir("wkt-road");
+    Writer::new(nodes, edges, RoadEdgeProperties::default())
+        .output_dir(&road)
+        .to_csv()
+        .unwrap();
+    assert_eq!(header(&road), "wkt");
+
+    let profile = RailwayEdgeProperties::default();
+    let (nodes, edges) = read(&test_data("switches.osm"), profile.clone()).unwrap();
+    let railway = output_dir("wkt-railway");
+    Writer::new(nodes, edges, profile)
+        .output_dir(&railway)
+        .to_csv()
+        .unwrap();
+    assert_eq!(header(&railway), "geometry");
+}
@@ -0,0 +1,86 @@
+mod common;
+
+use common::*;
+use osm4routing::*;
+use polars::prelude::*;
+
+// Dataframe and GeoParquet metadata of the file
+fn read_geoparquet(path: &std::path::Path) -> (DataFrame, serde_json::Value) {
+    let mut reader = ParquetReader::new(std::fs::File::open(path).unwrap());
+    let metadata = reader.get_metadata().unwrap().clone();
+    let geo = metadata
+        .key_value_metadata
+        .iter()
+        .flatten()
+        .find(|entry| entry.key == "geo")
+        .and_then(|entry| entry.value.clone())
+        .expect("the file has no geo metadata");
+    (
+        reader.finish().unwrap(),
+        serde_json::from_str(&geo).unwrap(),
+    )
+}
+
+#[test]
+fn geoparquet_schema_and_metadata() {
+    let profile = RoadEdgeProperties::default();
+    let (nodes, edges) = read(NETWORK, profile.clone()).unwrap();
+    let dir = output_dir("parquet");
+    let paths = Writer::new(nodes, edges, profile)
+        .output_dir(&dir)
+        .to_parquet()
+        .unwrap();
+    assert_eq!(
+        paths,
+        [dir.join("nodes.parquet"), dir.join("edges.parquet")]
+    );
+
+    let (nodes, geo) = read_geoparquet(&paths[0]);
+    assert_eq!(nodes.height(), 8);
+    assert_eq!(geo["version"], "1.0.0");
+    assert_eq!(geo["primary_column"], "geometry");
+    assert_eq!(geo["columns"]["geometry"]["encoding"], "WKB");
+    assert_eq!(
+        geo["columns"]["geometry"]["geometry_types"],
+        serde_json::json!(["Point"])
+    );
+    assert_eq!(nodes.column("id").unwrap().dtype(), &DataType::Int64);
+    assert_eq!(nodes.column("geometry").unwrap().dtype(), &DataType::Binary);
+
+    let (edges, geo) = read_geoparquet(&paths[1]);
+    assert_eq!(edges.height(), 8);
+    assert_eq!(
+        geo["columns"]["geometry"]["geometry_types"],
+        serde_json::json!(["LineString"])
+    );
+    let dtype = |name: &str| edges.column(name).unwrap().dtype().clone();
+    let names: Vec<&str> = edges
+        .get_column_names()
+        .iter()
+        .map(|name| name.as_str())
+        .collect();
+    assert_eq!(
+        names[..5],
+        ["edge_id", "source", "target", "osm_id", "osm_ids"]
+    );
+    assert_eq!(dtype("edge_id"), DataType::String);
+    assert_eq!(dtype("source"), DataType::Int64);
+    assert_eq!(dtype("target"), DataType::Int64);
+    assert_eq!(dtype("osm_ids"), DataType::List(Box::new(DataType::Int64)));
+    assert_eq!(dtype("length"), DataType::Float64);
+    assert!(matches!(dtype("car_forward"), DataType::Categorical(..)));
+    assert_eq!(dtype("maxspeed"), DataType::Float64);
+    assert_eq!(dtype("geometry"), DataType::Binary);
+
+    // Little endian WKB LineString with two points
+    let geometry = edges
+        .column("geometry")
+        .unwrap()
+        .binary()
+        .unwrap()
+        .get(0)
+        .unwrap();
+    assert_eq!(geometry[..5], [1, 2, 0, 0, 0]);
+    assert_eq!(u32::from_le_bytes(geometry[5..9].try_into().unwrap()), 2);
+    assert_eq!(geometry.len(), 9 + 2 * 16);
+}
@@ -0,0 +1,99 @@
+use osm4routing::edge::Edge;
+use osm4routing::*;
+use std::collections::BTreeMap;
+
+// Columns and values of every edge, by edge id
+fn rows<P: Profile>(edges: &[Edge<P>]) -> BTreeMap<String, Vec<(String, String)>> {
+    edges
+        .iter()
+        .map(|edge| {
+            let row = edge
+                .properties
+                .columns()
+                .into_iter()
+                .zip(edge.properties.row().iter().map(|value| value.to_string()))
+                .collect();
+            (edge.id.clone(), row)
+        })
+        .collect()
+}
+
+// The columns of the built-in profile that the profile file can't have are left out
+fn assert_equivalent<P: Profile>(
+    file: &str,
+    built_in: P,
+    profile_file: &str,
+    unsupported: &[&str],
+) {
+    let (_, expected) = read(file, built_in).unwrap();
+    let (_, edges) = read(file, RuleProfile::from_file(profile_file).unwrap()).unwrap();
+    assert!(!expected.is_empty());
+    assert_eq!(
+        expected[0].properties.geometry_column(),
+        edges[0].properties.geometry_column()
+    );
+    let mut expected_rows = rows(&expected);
+    for row in expected_rows.values_mut() {
+        row.retain(|(column, _)| !unsupported.contains(&column.as_str()));
+    }
+    assert_eq!(rows(&edges), expected_rows);
+    for (edge, expected) in edges.iter().zip(&expected) {
+        for mode in expected.properties.modes() {
+            assert_eq!(edge.properties.speed(mode), expected.properties.speed(mode));
+            assert_eq!(
+                edge.properties.forward(mode),
+                expected.properties.forward(mode)
+            );
+            assert_eq!(
+                edge.properties.backward(mode),
+                expected.properties.backward(mode)
+            );
+            assert_eq!(
+                edge.properties.cost_factor(mode),
+                expected.properties.cost_factor(mode)
+            );
+        }
+    }
+}
+
+#[test]
+fn railway_profile_file_is_equivalent() {
+    for file in ["railway.osm", "railway_tags.osm"] {
+        assert_equivalent(
+            &format!("src/osm4routing/test_data/{}", file),
+            RailwayEdgeProperties::default(),
+            "profiles/railway.toml",
+            &[],
+        );
+    }
+}
+
+const CONDITIONAL_COLUMNS: [&str; 3] = [
+    "access_conditional",
+    "maxspeed_conditional",
+    "oneway_conditional",
+];
+
+#[test]
+fn road_profile_file_is_equivalent() {
+    for file in ["road.osm", "road_tags.osm", "network.osm", "access.osm"] {
+        assert_equivalent(
+            &format!("src/osm4routing/test_data/{}", file),
+            RoadEdgeProperties::default(),
+            "profiles/road.toml",
+            &CONDITIONAL_COLUMNS,
+        );
+    }
+}
+
+#[test]
+fn road_profile_file_has_no_conditional_columns() {
+    let profile = RuleProfile::from_file("profiles/road.toml").unwrap();
+    let columns = profile.columns();
+    assert!(CONDITIONAL_COLUMNS
+        .iter()
+        .all(|column| !columns.iter().any(|c| c == column)));
+    // Without conditional restrictions, the way only opened by one is dropped
+    let (_, edges) = read("src/osm4routing/test_data/conditional.osm", profile).unwrap();
+    assert!(edges.is_empty());
+}
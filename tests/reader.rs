@@ -0,0 +1,72 @@
+mod common;
+
+use common::*;
+use osm4routing::*;
+
+const MISSING_NODES: &str = "src/osm4routing/test_data/missing_nodes.osm";
+
+#[test]
+fn split_ways_at_junctions() {
+    let (nodes, edges) = read(NETWORK, RoadEdgeProperties::default()).unwrap();
+    assert_eq!(node_ids(&nodes), vec![1, 2, 3, 4, 5, 6, 10, 11]);
+    assert_eq!(
+        edge_ids(&edges),
+        vec!["100-0", "100-1", "101-0", "101-1", "102-0", "103-0", "104-0", "200-0"]
+    );
+}
+
+#[test]
+fn missing_nodes_abort_by_default() {
+    let result = read(MISSING_NODES, RoadEdgeProperties::default());
+    match result {
+        Err(Error::MissingNode { way_id, node_id }) => {
+            assert_eq!((way_id, node_id), (WayId(2), NodeId(99)));
+        }
+        _ => panic!("the missing node must stop the reading"),
+    }
+    let message = Error::MissingNode {
+        way_id: WayId(2),
+        node_id: NodeId(99),
+    }
+    .to_string();
+    assert_eq!(
+        message,
+        "way 2 refers to node 99 that is missing from the extract"
+    );
+}
+
+#[test]
+fn missing_nodes_drop_the_way() {
+    let (nodes, edges) = Reader::new(RoadEdgeProperties::default())
+        .missing_nodes(MissingNodePolicy::DropWay)
+        .read(MISSING_NODES)
+        .unwrap();
+    assert_eq!(edge_ids(&edges), vec!["1-0"]);
+    assert_eq!(node_ids(&nodes), vec![1, 3]);
+}
+
+#[test]
+fn missing_nodes_truncate_the_way() {
+    let (nodes, edges) = Reader::new(RoadEdgeProperties::default())
+        .missing_nodes(MissingNodePolicy::TruncateWay)
+        .read(MISSING_NODES)
+        .unwrap();
+    // Way 2 is cut in two pieces around node 99, way 3 has a single known node
+    assert_eq!(edge_ids(&edges), vec!["1-0", "2-0", "2-1"]);
+    assert_eq!(node_ids(&nodes), vec![1, 3, 4, 5, 6]);
+    let pieces: Vec<(i64, i64)> = edges
+        .iter()
+        .filter(|edge| edge.osm_id == WayId(2))
+        .map(|edge| (edge.source.0, edge.target.0))
+        .collect();
+    assert_eq!(pieces, vec![(3, 4), (5, 6)]);
+}
+
+#[test]
+fn missing_file() {
+    let result = read(
+        "src/osm4routing/test_data/missing.osm.pbf",
+        RoadEdgeProperties::default(),
+    );
+    assert!(matches!(result, Err(Error::Io(_))));
+}
@@ -0,0 +1,34 @@
+mod common;
+
+use common::*;
+use osm4routing::*;
+
+#[test]
+fn restrictions_with_mode_and_conditions() {
+    let (_, _, restrictions) = Reader::new(RoadEdgeProperties::default())
+        .read_with_restrictions(NETWORK)
+        .unwrap();
+    assert_eq!(restrictions.len(), 2);
+    for restriction in &restrictions {
+        assert_eq!(restriction.from_edge, "102-0");
+        assert_eq!(restriction.via_node, Some(NodeId(1)));
+        assert_eq!(restriction.to_edge, "100-0");
+    }
+
+    let always = restrictions.iter().find(|r| r.mode.is_empty()).unwrap();
+    assert_eq!(always.restriction, "no_right_turn");
+    assert!(always.conditions.is_empty());
+
+    let hgv = restrictions.iter().find(|r| r.mode == "hgv").unwrap();
+    assert_eq!(hgv.restriction, "no_left_turn");
+    let monday_noon = chrono::NaiveDate::from_ymd_opt(2024, 6, 3)
+        .unwrap()
+        .and_hms_opt(12, 0, 0)
+        .unwrap();
+    let sunday_noon = chrono::NaiveDate::from_ymd_opt(2024, 6, 9)
+        .unwrap()
+        .and_hms_opt(12, 0, 0)
+        .unwrap();
+    assert!(hgv.holds(&monday_noon));
+    assert!(!hgv.holds(&sunday_noon));
+}
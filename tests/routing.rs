@@ -0,0 +1,80 @@
+mod common;
+
+use common::*;
+use osm4routing::*;
+
+fn graph() -> Graph<RoadEdgeProperties> {
+    let (nodes, edges) = read(NETWORK, RoadEdgeProperties::default()).unwrap();
+    Graph::new(nodes, edges)
+}
+
+fn route_ids(route: &Route) -> Vec<&str> {
+    route
+        .edges
+        .iter()
+        .map(|edge| edge.edge_id.as_str())
+        .collect()
+}
+
+#[test]
+fn shortest_path_follows_oneways() {
+    let graph = graph();
+    let route = graph
+        .dijkstra(NodeId(2), NodeId(5), Some(Mode::Car))
+        .unwrap();
+    assert_eq!(route_ids(&route), vec!["104-0"]);
+    assert!(route.edges[0].forward);
+
+    // The car can't go back along the oneway 104
+    let route = graph
+        .dijkstra(NodeId(5), NodeId(2), Some(Mode::Car))
+        .unwrap();
+    assert_eq!(route.edges.len(), 3);
+    assert!(!route_ids(&route).contains(&"104-0"));
+
+    let route = graph
+        .dijkstra(NodeId(5), NodeId(2), Some(Mode::Foot))
+        .unwrap();
+    assert_eq!(route_ids(&route), vec!["104-0"]);
+    assert!(!route.edges[0].forward);
+}
+
+#[test]
+fn astar_finds_the_same_cost_as_dijkstra() {
+    let graph = graph();
+    for mode in [None, Some(Mode::Car), Some(Mode::Foot)] {
+        for (source, target) in [(1, 6), (6, 1), (5, 2), (3, 4)] {
+            let (source, target) = (NodeId(source), NodeId(target));
+            let dijkstra = graph.dijkstra(source, target, mode).unwrap();
+            let astar = graph.astar(source, target, mode).unwrap();
+            assert!((dijkstra.cost - astar.cost).abs() < 1e-6);
+            assert!((dijkstra.length - astar.length).abs() < 1e-6);
+        }
+    }
+}
+
+#[test]
+fn route_geometry_joins_the_edges() {
+    let graph = graph();
+    let route = graph.dijkstra(NodeId(1), NodeId(3), None).unwrap();
+    assert_eq!(route_ids(&route), vec!["100-0", "100-1"]);
+    assert_eq!(route.geometry.len(), 3);
+    assert!((route.geometry[0].lon - 2.).abs() < 1e-9);
+    assert!((route.geometry[2].lon - 2.004).abs() < 1e-9);
+    assert!(route.length > 250. && route.length < 350.);
+
+    let route = graph.dijkstra(NodeId(1), NodeId(1), None).unwrap();
+    assert!(route.edges.is_empty());
+    assert_eq!(route.geometry.len(), 1);
+}
+
+#[test]
+fn unreachable_targets() {
+    let graph = graph();
+    assert!(graph.dijkstra(NodeId(1), NodeId(10), None).is_none());
+    assert!(graph
+        .astar(NodeId(1), NodeId(10), Some(Mode::Car))
+        .is_none());
+    // Unknown node
+    assert!(graph.dijkstra(NodeId(1), NodeId(42), None).is_none());
+}
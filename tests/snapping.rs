@@ -0,0 +1,95 @@
+mod common;
+
+use common::*;
+use osm4routing::coord::Coord;
+use osm4routing::*;
+
+fn graph() -> Graph<RoadEdgeProperties> {
+    let (nodes, edges) = read(NETWORK, RoadEdgeProperties::default()).unwrap();
+    Graph::new(nodes, edges)
+}
+
+#[test]
+fn nearest_edges_by_distance() {
+    let graph = graph();
+    // 11 m east of the middle of 104
+    let coord = Coord {
+        lon: 2.00215,
+        lat: 48.0005,
+    };
+    let snaps = graph.nearest_edges(&coord, 3, None, None);
+    assert_eq!(snaps.len(), 3);
+    assert_eq!(snaps[0].edge_id, "104-0");
+    assert!((snaps[0].fraction - 0.5).abs() < 0.01);
+    assert!(
+        (snaps[0].distance - 11.2).abs() < 0.5,
+        "{}",
+        snaps[0].distance
+    );
+    assert!((snaps[0].point.lon - 2.002).abs() < 1e-9);
+    assert!(snaps
+        .windows(2)
+        .all(|pair| pair[0].distance <= pair[1].distance));
+    // Each edge only once, at its closest point
+    let mut ids: Vec<&str> = snaps.iter().map(|snap| snap.edge_id.as_str()).collect();
+    ids.dedup();
+    assert_eq!(ids.len(), 3);
+}
+
+#[test]
+fn snapped_on_the_extremities() {
+    let graph = graph();
+    // West of node 1, beyond the end of 100-0
+    let coord = Coord {
+        lon: 1.999,
+        lat: 48.0,
+    };
+    let snap = &graph.nearest_edges(&coord, 1, None, None)[0];
+    assert!(["100-0", "102-0"].contains(&snap.edge_id.as_str()));
+    assert!((snap.point.lon - 2.).abs() < 1e-9 && (snap.point.lat - 48.).abs() < 1e-9);
+    // Both edges start at node 1
+    assert_eq!(snap.fraction, 0.);
+}
+
+#[test]
+fn only_the_edges_of_the_mode() {
+    let (nodes, edges) = read(&test_data("road_tags.osm"), RoadEdgeProperties::default()).unwrap();
+    let graph = Graph::new(nodes, edges);
+    let coord = Coord {
+        lon: 2.0001,
+        lat: 48.005,
+    };
+    let car = graph.nearest_edges(&coord, 20, None, Some(Mode::Car));
+    let foot = graph.nearest_edges(&coord, 20, None, Some(Mode::Foot));
+    let all = graph.nearest_edges(&coord, 20, None, None);
+    assert_eq!(all.len(), graph.edges.len());
+    assert!(car.len() < all.len());
+    for snap in &car {
+        assert!(graph.accessible(snap.edge, Some(Mode::Car)));
+    }
+    // The footway with access=no can't be used by anyone
+    assert!(!car.iter().any(|snap| snap.edge_id == "30-0"));
+    assert!(!foot.iter().any(|snap| snap.edge_id == "30-0"));
+}
+
+#[test]
+fn within_the_max_distance() {
+    let graph = graph();
+    let coord = Coord {
+        lon: 2.00215,
+        lat: 48.0005,
+    };
+    let snaps = graph.nearest_edges(&coord, 20, Some(50.), None);
+    assert!(!snaps.is_empty());
+    assert!(snaps.len() < graph.nearest_edges(&coord, 20, None, None).len());
+    assert!(snaps.iter().all(|snap| snap.distance <= 50.));
+    assert!(graph.nearest_edges(&coord, 20, Some(5.), None).is_empty());
+
+    // Far from the network, nothing is found instead of some edge kilometers away
+    let far = Coord {
+        lon: 2.1,
+        lat: 48.1,
+    };
+    assert!(graph.nearest_edges(&far, 1, Some(50.), None).is_empty());
+    assert_eq!(graph.nearest_edges(&far, 1, None, None).len(), 1);
+}
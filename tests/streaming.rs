@@ -0,0 +1,129 @@
+mod common;
+
+use common::*;
+use osm4routing::edge::Edge;
+use osm4routing::node::Node;
+use osm4routing::*;
+
+// Records the order of the calls
+#[derive(Default)]
+struct Recorder {
+    events: Vec<String>,
+}
+
+impl NodeSink for Recorder {
+    fn push_node(&mut self, node: Node) -> Result<(), Error> {
+        self.events.push(format!("node {}", node.id.0));
+        Ok(())
+    }
+
+    fn finish_nodes(&mut self) -> Result<(), Error> {
+        self.events.push("finish nodes".to_string());
+        Ok(())
+    }
+}
+
+impl<P> EdgeSink<P> for Recorder {
+    fn push_edge(&mut self, edge: Edge<P>) -> Result<(), Error> {
+        self.events.push(format!("edge {}", edge.id));
+        Ok(())
+    }
+
+    fn finish_edges(&mut self) -> Result<(), Error> {
+        self.events.push("finish edges".to_string());
+        Ok(())
+    }
+}
+
+#[test]
+fn nodes_then_edges() {
+    for contract in [false, true] {
+        let mut recorder = Recorder::default();
+        Reader::new(RoadEdgeProperties::default())
+            .contract(contract)
+            .read_into(NETWORK, &mut recorder)
+            .unwrap();
+        let (nodes, edges) = Reader::new(RoadEdgeProperties::default())
+            .contract(contract)
+            .read(NETWORK)
+            .unwrap();
+
+        let mut expected: Vec<String> = nodes
+            .iter()
+            .map(|node| format!("node {}", node.id.0))
+            .collect();
+        expected.push("finish nodes".to_string());
+        expected.extend(edges.iter().map(|edge| format!("edge {}", edge.id)));
+        expected.push("finish edges".to_string());
+        assert_eq!(recorder.events, expected);
+    }
+}
+
+#[test]
+fn sink_errors_stop_the_reading() {
+    struct Failing;
+    impl NodeSink for Failing {
+        fn push_node(&mut self, _: Node) -> Result<(), Error> {
+            Err(Error::Clip("full".to_string()))
+        }
+    }
+    impl<P> EdgeSink<P> for Failing {
+        fn push_edge(&mut self, _: Edge<P>) -> Result<(), Error> {
+            Ok(())
+        }
+    }
+    let result = Reader::new(RoadEdgeProperties::default()).read_into(NETWORK, &mut Failing);
+    assert!(matches!(result, Err(Error::Clip(_))));
+}
+
+#[test]
+fn streamed_csv_matches_the_writer() {
+    let profile = RoadEdgeProperties::default();
+    let streamed = output_dir("streamed");
+    let mut sink = CsvSink::new(&profile, &streamed, "").unwrap();
+    let relations = Reader::new(profile.clone())
+        .read_into(NETWORK, &mut sink)
+        .unwrap();
+    sink.restrictions(&relations.restrictions).unwrap();
+    let streamed_paths = sink.finish().unwrap();
+
+    let (nodes, edges, restrictions) = Reader::new(profile.clone())
+        .read_with_restrictions(NETWORK)
+        .unwrap();
+    let written = output_dir("written");
+    let paths = Writer::new(nodes, edges, profile)
+        .restrictions(restrictions)
+        .output_dir(&written)
+        .to_csv()
+        .unwrap();
+
+    assert_eq!(streamed_paths.len(), paths.len());
+    for (streamed, written) in streamed_paths.iter().zip(&paths) {
+        assert_eq!(streamed.file_name(), written.file_name());
+        assert_eq!(
+            std::fs::read_to_string(streamed).unwrap(),
+            std::fs::read_to_string(written).unwrap()
+        );
+    }
+}
+
+#[test]
+fn failed_read_leaves_the_csv_files_untouched() {
+    let dir = output_dir("failed-read");
+    std::fs::write(dir.join("edges.csv"), "previous extract\n").unwrap();
+    let profile = RoadEdgeProperties::default();
+    let mut sink = CsvSink::new(&profile, &dir, "").unwrap();
+    let result = Reader::new(profile).read_into(&test_data("missing_nodes.osm"), &mut sink);
+    assert!(matches!(result, Err(Error::MissingNode { .. })));
+    drop(sink);
+
+    let files: Vec<String> = std::fs::read_dir(&dir)
+        .unwrap()
+        .map(|entry| entry.unwrap().file_name().into_string().unwrap())
+        .collect();
+    assert_eq!(files, ["edges.csv"]);
+    assert_eq!(
+        std::fs::read_to_string(dir.join("edges.csv")).unwrap(),
+        "previous extract\n"
+    );
+}
@@ -0,0 +1,62 @@
+mod common;
+
+use common::*;
+use osm4routing::*;
+
+#[test]
+fn length_and_travel_times() {
+    let (_, edges) = read(NETWORK, RoadEdgeProperties::default()).unwrap();
+    let edge = edges.iter().find(|edge| edge.id == "100-0").unwrap();
+    // 0.002° of longitude at 48°N
+    let expected = 0.002_f64.to_radians() * 6_371_000. * 48_f64.to_radians().cos();
+    assert!((edge.length() - expected).abs() < 1., "{}", edge.length());
+
+    for mode in [Mode::Foot, Mode::Bike, Mode::Car] {
+        let speed = edge.properties.speed(mode).unwrap();
+        let time = edge.travel_time(mode).unwrap();
+        assert!((time - edge.length() / (speed / 3.6)).abs() < 1e-9);
+    }
+    let foot = edge.travel_time(Mode::Foot).unwrap();
+    let car = edge.travel_time(Mode::Car).unwrap();
+    assert!(foot > car);
+
+    // Railway edges have no mode, so no travel time
+    let (_, edges) = read(&test_data("railway.osm"), RailwayEdgeProperties::default()).unwrap();
+    assert!(edges[0].length() > 0.);
+    assert!(edges[0].properties.modes().is_empty());
+}
+
+#[test]
+fn weight_columns() {
+    let profile = RoadEdgeProperties::default();
+    let (nodes, edges) = read(NETWORK, profile.clone()).unwrap();
+    let dir = output_dir("weights");
+    let paths = Writer::new(nodes, edges.clone(), profile)
+        .output_dir(&dir)
+        .to_csv()
+        .unwrap();
+    let mut csv = csv::Reader::from_path(&paths[1]).unwrap();
+    let headers = csv.headers().unwrap().clone();
+    let column = |name: &str| headers.iter().position(|header| header == name).unwrap();
+    let (length, foot_time, bike_time, car_time) = (
+        column("length"),
+        column("foot_time"),
+        column("bike_time"),
+        column("car_time"),
+    );
+    assert!(length < foot_time && foot_time < bike_time && bike_time < car_time);
+    assert_eq!(
+        headers.iter().take(4).collect::<Vec<_>>(),
+        ["edge_id", "source", "target", "osm_ids"]
+    );
+
+    for (record, edge) in csv.records().zip(&edges) {
+        let record = record.unwrap();
+        assert_eq!(record[0], edge.id);
+        assert_eq!(record[1], edge.source.0.to_string());
+        assert_eq!(record[2], edge.target.0.to_string());
+        let value = |i: usize| record[i].parse::<f64>().unwrap();
+        assert!((value(length) - edge.length()).abs() < 1e-6);
+        assert!((value(car_time) - edge.travel_time(Mode::Car).unwrap()).abs() < 1e-6);
+    }
+}